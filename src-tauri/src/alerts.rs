@@ -0,0 +1,603 @@
+use crate::{AppState, ProcessInfo, SystemStats};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, State};
+
+pub const FIRED_EVENT: &str = "alert-fired";
+pub const RESOLVED_EVENT: &str = "alert-resolved";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    /// Percent; the mean of all cores for system rules
+    CpuUsage,
+    /// Percent of total physical memory
+    MemoryPercent,
+    MemoryBytes,
+    /// One minute load average, system rules only
+    LoadAverage,
+    /// Percent of the root filesystem, system rules only
+    DiskUsedPercent,
+    /// Bytes per second, system rules only
+    NetworkRxRate,
+    NetworkTxRate,
+    /// Bytes per second, process rules only
+    DiskReadRate,
+    DiskWriteRate,
+    /// Thread count, process rules only
+    Threads,
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+impl AlertMetric {
+    fn system_value(self, stats: &SystemStats) -> Option<f64> {
+        Some(match self {
+            Self::CpuUsage => {
+                let cores = stats.cpu_usage.len().max(1);
+                stats.cpu_usage.iter().map(|usage| f64::from(*usage)).sum::<f64>() / cores as f64
+            }
+            Self::MemoryPercent => percent(stats.memory_used, stats.memory_total),
+            Self::MemoryBytes => stats.memory_used as f64,
+            Self::LoadAverage => stats.load_avg[0],
+            Self::DiskUsedPercent => percent(stats.disk_used_bytes, stats.disk_total_bytes),
+            Self::NetworkRxRate => stats.network_rx_bytes as f64,
+            Self::NetworkTxRate => stats.network_tx_bytes as f64,
+            Self::DiskReadRate | Self::DiskWriteRate | Self::Threads => return None,
+        })
+    }
+
+    fn process_value(self, process: &ProcessInfo, stats: &SystemStats) -> Option<f64> {
+        Some(match self {
+            Self::CpuUsage => f64::from(process.cpu_usage),
+            Self::MemoryPercent => percent(process.memory_usage, stats.memory_total),
+            Self::MemoryBytes => process.memory_usage as f64,
+            Self::DiskReadRate => process.disk_io.read_bytes_per_sec,
+            Self::DiskWriteRate => process.disk_io.write_bytes_per_sec,
+            Self::Threads => f64::from(process.threads?),
+            Self::LoadAverage | Self::DiskUsedPercent | Self::NetworkRxRate | Self::NetworkTxRate => {
+                return None
+            }
+        })
+    }
+
+    fn applies_to_processes(self) -> bool {
+        !matches!(
+            self,
+            Self::LoadAverage | Self::DiskUsedPercent | Self::NetworkRxRate | Self::NetworkTxRate
+        )
+    }
+
+    fn applies_to_system(self) -> bool {
+        !matches!(self, Self::DiskReadRate | Self::DiskWriteRate | Self::Threads)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    #[default]
+    Above,
+    Below,
+}
+
+impl Comparison {
+    fn crossed(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Self::Above => value > threshold,
+            Self::Below => value < threshold,
+        }
+    }
+
+    // The level the value has to get back past before an alert resolves
+    fn recovery_level(self, threshold: f64, hysteresis: f64) -> f64 {
+        match self {
+            Self::Above => threshold - hysteresis,
+            Self::Below => threshold + hysteresis,
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AlertRule {
+    /// Assigned when the rule is first saved
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Case-insensitive substring of the process name. Rules without one watch the whole system.
+    #[serde(default)]
+    pub process_name: Option<String>,
+    pub metric: AlertMetric,
+    #[serde(default)]
+    pub comparison: Comparison,
+    pub threshold: f64,
+    /// How long the threshold has to stay crossed before the alert fires
+    #[serde(default)]
+    pub duration_secs: u64,
+    /// How far back past the threshold the value has to go before the alert resolves
+    #[serde(default)]
+    pub hysteresis: f64,
+    /// Quiet time after an alert resolves before the same rule can fire for the same subject again
+    #[serde(default)]
+    pub cooldown_secs: u64,
+}
+
+impl AlertRule {
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Alert rules need a name".to_string());
+        }
+        if !self.threshold.is_finite() {
+            return Err("Alert threshold must be a number".to_string());
+        }
+        if !self.hysteresis.is_finite() || self.hysteresis < 0.0 {
+            return Err("Alert hysteresis must be zero or positive".to_string());
+        }
+        let supported = match self.process_name {
+            Some(_) => self.metric.applies_to_processes(),
+            None => self.metric.applies_to_system(),
+        };
+        if !supported {
+            return Err(format!(
+                "{:?} is not available for {} rules",
+                self.metric,
+                if self.process_name.is_some() { "process" } else { "system" }
+            ));
+        }
+        Ok(())
+    }
+
+    fn matches(&self, process: &ProcessInfo) -> bool {
+        self.process_name.as_ref().is_some_and(|pattern| {
+            process.name.to_lowercase().contains(&pattern.to_lowercase())
+        })
+    }
+}
+
+/// Payload of the alert-fired and alert-resolved events
+#[derive(Serialize, Debug, Clone)]
+pub struct AlertEvent {
+    pub rule_id: String,
+    pub rule_name: String,
+    /// Set for process rules, each matching process alerts on its own
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+    pub metric: AlertMetric,
+    pub threshold: f64,
+    pub value: f64,
+    pub fired_at: u64,
+    pub resolved_at: Option<u64>,
+}
+
+// (rule id, (pid, start time)) with no process part for system rules
+type SubjectKey = (String, Option<(u32, u64)>);
+
+#[derive(Default)]
+struct SubjectState {
+    breached_since: Option<u64>,
+    firing: Option<AlertEvent>,
+    resolved_at: Option<u64>,
+}
+
+// The first "rule-N" that is not taken yet
+fn unused_id(taken: impl Fn(&str) -> bool) -> String {
+    (1..)
+        .map(|n| format!("rule-{}", n))
+        .find(|id| !taken(id))
+        .unwrap_or_default()
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct AlertConfig {
+    rules: Vec<AlertRule>,
+}
+
+#[derive(Default)]
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    subjects: HashMap<SubjectKey, SubjectState>,
+    config_path: Option<PathBuf>,
+    app: Option<AppHandle>,
+}
+
+impl AlertEngine {
+    /// Connects the engine to the window for events and loads the saved rules.
+    pub fn attach(&mut self, app: AppHandle, config_path: PathBuf) -> Result<(), String> {
+        self.app = Some(app);
+        self.config_path = Some(config_path.clone());
+        let contents = match std::fs::read_to_string(&config_path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(format!("Failed to read {}: {}", config_path.display(), e)),
+        };
+        let config: AlertConfig = serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {}: {}", config_path.display(), e))?;
+        let (assigned_ids, invalid) = self.load(config.rules);
+        if assigned_ids {
+            self.save()?;
+        }
+        if !invalid.is_empty() {
+            return Err(format!(
+                "Disabled invalid alert rules in {}: {}",
+                config_path.display(),
+                invalid.join("; ")
+            ));
+        }
+        Ok(())
+    }
+
+    // Rules from disk get the same checks as save_alert_rule. Missing and duplicate ids are
+    // replaced so no two rules share state, and invalid rules are kept but disabled so they
+    // stay in the file for the user to fix. Returns whether any id changed and the problems.
+    fn load(&mut self, mut rules: Vec<AlertRule>) -> (bool, Vec<String>) {
+        let mut taken: HashSet<String> = HashSet::new();
+        let unnamed: Vec<usize> = (0..rules.len())
+            .filter(|&index| rules[index].id.is_empty() || !taken.insert(rules[index].id.clone()))
+            .collect();
+        for &index in &unnamed {
+            let id = unused_id(|id| taken.contains(id));
+            taken.insert(id.clone());
+            rules[index].id = id;
+        }
+
+        let mut invalid = Vec::new();
+        for rule in &mut rules {
+            if let Err(e) = rule.validate() {
+                invalid.push(format!("{}: {}", rule.id, e));
+                rule.enabled = false;
+            }
+        }
+        self.rules = rules;
+        self.subjects.clear();
+        (!unnamed.is_empty(), invalid)
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.config_path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let config = AlertConfig {
+            rules: self.rules.clone(),
+        };
+        let contents = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+        // Write beside the file and rename over it, so a crash never leaves half a config
+        let mut temp = path.clone().into_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        std::fs::write(&temp, contents).map_err(|e| format!("Failed to write {}: {}", temp.display(), e))?;
+        std::fs::rename(&temp, path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    fn emit(&self, event: &str, alert: &AlertEvent) {
+        if let Some(app) = &self.app {
+            let _ = app.emit(event, alert.clone());
+        }
+    }
+
+    /// Steps every rule with one sample, firing and resolving alerts as needed.
+    pub fn evaluate(&mut self, timestamp: u64, processes: &[ProcessInfo], stats: &SystemStats) {
+        let (fired, resolved) = self.step(timestamp, processes, stats);
+        for alert in &fired {
+            self.emit(FIRED_EVENT, alert);
+        }
+        for alert in &resolved {
+            self.emit(RESOLVED_EVENT, alert);
+        }
+    }
+
+    // Returns the alerts that fired and resolved on this sample
+    fn step(
+        &mut self,
+        timestamp: u64,
+        processes: &[ProcessInfo],
+        stats: &SystemStats,
+    ) -> (Vec<AlertEvent>, Vec<AlertEvent>) {
+        let mut seen: HashSet<SubjectKey> = HashSet::new();
+        let mut fired = Vec::new();
+        let mut resolved = Vec::new();
+
+        for rule in self.rules.iter().filter(|rule| rule.enabled) {
+            let subjects: Vec<(Option<&ProcessInfo>, f64)> = match rule.process_name {
+                None => rule
+                    .metric
+                    .system_value(stats)
+                    .map(|value| (None, value))
+                    .into_iter()
+                    .collect(),
+                Some(_) => processes
+                    .iter()
+                    .filter(|process| rule.matches(process))
+                    .filter_map(|process| Some((Some(process), rule.metric.process_value(process, stats)?)))
+                    .collect(),
+            };
+
+            for (process, value) in subjects {
+                let key = (rule.id.clone(), process.map(|p| (p.pid, p.start_time)));
+                let subject = self.subjects.entry(key.clone()).or_default();
+                seen.insert(key);
+
+                if let Some(mut alert) = subject.firing.take() {
+                    alert.value = value;
+                    let level = rule.comparison.recovery_level(rule.threshold, rule.hysteresis);
+                    if rule.comparison.crossed(value, level) {
+                        subject.firing = Some(alert);
+                    } else {
+                        alert.resolved_at = Some(timestamp);
+                        subject.resolved_at = Some(timestamp);
+                        subject.breached_since = None;
+                        resolved.push(alert);
+                    }
+                    continue;
+                }
+
+                if !rule.comparison.crossed(value, rule.threshold) {
+                    subject.breached_since = None;
+                    continue;
+                }
+                let since = *subject.breached_since.get_or_insert(timestamp);
+                let held = timestamp.saturating_sub(since) >= rule.duration_secs.saturating_mul(1000);
+                let cooled = subject
+                    .resolved_at
+                    .is_none_or(|at| timestamp.saturating_sub(at) >= rule.cooldown_secs.saturating_mul(1000));
+                if held && cooled {
+                    let alert = AlertEvent {
+                        rule_id: rule.id.clone(),
+                        rule_name: rule.name.clone(),
+                        pid: process.map(|p| p.pid),
+                        process_name: process.map(|p| p.name.clone()),
+                        metric: rule.metric,
+                        threshold: rule.threshold,
+                        value,
+                        fired_at: timestamp,
+                        resolved_at: None,
+                    };
+                    subject.firing = Some(alert.clone());
+                    fired.push(alert);
+                }
+            }
+        }
+
+        // Exited processes and removed or disabled rules resolve whatever they had firing
+        let now_unseen: Vec<SubjectKey> = self
+            .subjects
+            .keys()
+            .filter(|key| !seen.contains(*key))
+            .cloned()
+            .collect();
+        for key in now_unseen {
+            let still_watched = key.1.is_none() && self.rules.iter().any(|rule| rule.id == key.0 && rule.enabled);
+            if still_watched {
+                continue;
+            }
+            if let Some(mut alert) = self.subjects.remove(&key).and_then(|subject| subject.firing) {
+                alert.resolved_at = Some(timestamp);
+                resolved.push(alert);
+            }
+        }
+        (fired, resolved)
+    }
+
+    fn active(&self) -> Vec<AlertEvent> {
+        let mut active: Vec<AlertEvent> = self
+            .subjects
+            .values()
+            .filter_map(|subject| subject.firing.clone())
+            .collect();
+        active.sort_by_key(|alert| alert.fired_at);
+        active
+    }
+
+    fn upsert(&mut self, mut rule: AlertRule) -> Result<AlertRule, String> {
+        rule.validate()?;
+        if rule.id.is_empty() {
+            rule.id = unused_id(|id| self.rules.iter().any(|existing| existing.id == id));
+        }
+        match self.rules.iter_mut().find(|existing| existing.id == rule.id) {
+            Some(existing) => {
+                *existing = rule.clone();
+                // A changed rule starts over rather than carrying state from its old thresholds
+                self.subjects.retain(|(id, _), subject| *id != rule.id || subject.firing.is_some());
+            }
+            None => self.rules.push(rule.clone()),
+        }
+        self.save()?;
+        Ok(rule)
+    }
+
+    fn remove(&mut self, id: &str) -> Result<(), String> {
+        let count = self.rules.len();
+        self.rules.retain(|rule| rule.id != id);
+        if self.rules.len() == count {
+            return Err(format!("No alert rule with id {}", id));
+        }
+        self.save()
+    }
+}
+
+#[tauri::command]
+pub async fn get_alert_rules(state: State<'_, AppState>) -> Result<Vec<AlertRule>, String> {
+    let alerts = state.alerts.lock().map_err(|_| "Failed to lock alerts")?;
+    Ok(alerts.rules.clone())
+}
+
+/// Adds a rule, or replaces the rule with the same id. Returns the saved rule.
+#[tauri::command]
+pub async fn save_alert_rule(rule: AlertRule, state: State<'_, AppState>) -> Result<AlertRule, String> {
+    state.alerts.lock().map_err(|_| "Failed to lock alerts")?.upsert(rule)
+}
+
+#[tauri::command]
+pub async fn remove_alert_rule(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.alerts.lock().map_err(|_| "Failed to lock alerts")?.remove(&id)
+}
+
+#[tauri::command]
+pub async fn get_active_alerts(state: State<'_, AppState>) -> Result<Vec<AlertEvent>, String> {
+    let alerts = state.alerts.lock().map_err(|_| "Failed to lock alerts")?;
+    Ok(alerts.active())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(json: &str) -> Vec<AlertRule> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn stats() -> SystemStats {
+        SystemStats {
+            cpu_usage: Vec::new(),
+            memory_total: 0,
+            memory_used: 0,
+            memory_free: 0,
+            memory_cached: 0,
+            uptime: 0,
+            load_avg: [0.0; 3],
+            network_rx_bytes: 0,
+            network_tx_bytes: 0,
+            disk_total_bytes: 0,
+            disk_used_bytes: 0,
+            disk_free_bytes: 0,
+            process_states: Default::default(),
+        }
+    }
+
+    fn process(pid: u32, cpu_usage: f32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            cpu_usage,
+            name: "worker".to_string(),
+            ..ProcessInfo::default()
+        }
+    }
+
+    // A process rule on CPU above 50%, with the given extra fields
+    fn engine(extra: &str) -> AlertEngine {
+        let mut engine = AlertEngine::default();
+        let rule = format!(
+            r#"[{{"id": "cpu", "name": "cpu", "process_name": "work", "metric": "cpu_usage",
+                  "threshold": 50 {}}}]"#,
+            extra
+        );
+        assert_eq!(engine.load(rules(&rule)), (false, Vec::new()));
+        engine
+    }
+
+    // Steps the engine with one process at `cpu_usage` and counts what fired and resolved
+    fn step(engine: &mut AlertEngine, timestamp: u64, cpu_usage: f32) -> (usize, usize) {
+        let (fired, resolved) = engine.step(timestamp, &[process(1, cpu_usage)], &stats());
+        (fired.len(), resolved.len())
+    }
+
+    #[test]
+    fn fires_only_once_the_breach_is_held() {
+        let mut engine = engine(r#", "duration_secs": 2"#);
+        assert_eq!(step(&mut engine, 0, 90.0), (0, 0));
+        assert_eq!(step(&mut engine, 1_000, 90.0), (0, 0));
+        // Dipping below restarts the clock
+        assert_eq!(step(&mut engine, 2_000, 10.0), (0, 0));
+        assert_eq!(step(&mut engine, 3_000, 90.0), (0, 0));
+        assert_eq!(step(&mut engine, 5_000, 90.0), (1, 0));
+        assert_eq!(step(&mut engine, 6_000, 90.0), (0, 0));
+        let active = engine.active();
+        assert_eq!((active.len(), active[0].fired_at, active[0].pid), (1, 5_000, Some(1)));
+    }
+
+    #[test]
+    fn resolves_only_past_the_hysteresis() {
+        let mut engine = engine(r#", "hysteresis": 10"#);
+        assert_eq!(step(&mut engine, 0, 60.0), (1, 0));
+        assert_eq!(step(&mut engine, 1_000, 45.0), (0, 0));
+        assert_eq!(engine.active()[0].value, 45.0);
+        assert_eq!(step(&mut engine, 2_000, 40.0), (0, 1));
+        assert!(engine.active().is_empty());
+    }
+
+    #[test]
+    fn cooldown_holds_back_refiring() {
+        let mut engine = engine(r#", "cooldown_secs": 10"#);
+        assert_eq!(step(&mut engine, 0, 90.0), (1, 0));
+        assert_eq!(step(&mut engine, 1_000, 10.0), (0, 1));
+        assert_eq!(step(&mut engine, 2_000, 90.0), (0, 0));
+        assert_eq!(step(&mut engine, 10_000, 90.0), (0, 0));
+        assert_eq!(step(&mut engine, 11_000, 90.0), (1, 0));
+    }
+
+    #[test]
+    fn exited_processes_and_disabled_rules_resolve() {
+        let mut engine = engine("");
+        assert_eq!(step(&mut engine, 0, 90.0), (1, 0));
+        let (fired, resolved) = engine.step(1_000, &[], &stats());
+        assert_eq!((fired.len(), resolved.len()), (0, 1));
+        assert_eq!(resolved[0].resolved_at, Some(1_000));
+
+        assert_eq!(step(&mut engine, 2_000, 90.0), (1, 0));
+        engine.rules[0].enabled = false;
+        assert_eq!(step(&mut engine, 3_000, 90.0), (0, 1));
+        assert!(engine.active().is_empty());
+    }
+
+    #[test]
+    fn saving_replaces_the_file() {
+        let dir = std::env::temp_dir().join(format!("neohtop-alerts-{}", std::process::id()));
+        let path = dir.join("alerts.json");
+        let mut engine = engine("");
+        engine.config_path = Some(path.clone());
+        engine.save().unwrap();
+        engine.rules[0].name = "renamed".to_string();
+        engine.save().unwrap();
+
+        let saved: AlertConfig = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(saved.rules[0].name, "renamed");
+        assert_eq!(files, ["alerts.json"]);
+    }
+
+    #[test]
+    fn loading_assigns_missing_and_duplicate_ids() {
+        let mut engine = AlertEngine::default();
+        let (assigned, invalid) = engine.load(rules(
+            r#"[
+                {"name": "a", "metric": "cpu_usage", "threshold": 90},
+                {"id": "rule-1", "name": "b", "metric": "cpu_usage", "threshold": 90},
+                {"id": "rule-1", "name": "c", "metric": "cpu_usage", "threshold": 90}
+            ]"#,
+        ));
+        assert!(assigned && invalid.is_empty());
+        let ids: Vec<&str> = engine.rules.iter().map(|rule| rule.id.as_str()).collect();
+        assert_eq!(ids, ["rule-2", "rule-1", "rule-3"]);
+    }
+
+    #[test]
+    fn loading_disables_invalid_rules() {
+        let mut engine = AlertEngine::default();
+        let (assigned, invalid) = engine.load(rules(
+            r#"[
+                {"id": "cpu", "name": "cpu", "metric": "cpu_usage", "threshold": 90},
+                {"id": "load", "name": "load", "process_name": "x", "metric": "load_average", "threshold": 1}
+            ]"#,
+        ));
+        assert!(!assigned);
+        assert_eq!(invalid.len(), 1);
+        assert!(invalid[0].starts_with("load: "), "{}", invalid[0]);
+        assert!(engine.rules[0].enabled && !engine.rules[1].enabled);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::export::{self, CsvLayout, ExportFormat};
+use crate::filter::Filter;
+use crate::{read_processes, unix_millis, AppState};
+use std::io::BufWriter;
+use std::time::{Duration, Instant};
+use sysinfo::{System, SystemExt};
+
+const USAGE: &str = "Usage: NeoHtop --batch [--interval SECONDS] [--count N] [--format json|ndjson|csv] [--columns a,b,...] [--filter EXPR]
+
+Prints process and system snapshots to stdout without opening a window.
+
+  --interval SECONDS  Time between snapshots, default 2
+  --count N           Number of snapshots to print, default is to run until interrupted
+  --format FORMAT     json (default), ndjson or csv
+  --columns LIST      Comma separated ProcessInfo fields to include, default all
+  --filter EXPR       Only print processes matching a filter, e.g. 'cpu_usage > 10 and user == \"postgres\"'";
+
+pub struct BatchOptions {
+    pub interval: Duration,
+    pub count: Option<u64>,
+    pub format: ExportFormat,
+    pub columns: Option<Vec<String>>,
+    pub filter: Option<Filter>,
+}
+
+/// Returns true when the arguments ask for batch mode instead of the window.
+pub fn requested(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--batch")
+}
+
+fn parse_args(args: &[String]) -> Result<BatchOptions, String> {
+    let mut options = BatchOptions {
+        interval: Duration::from_secs(2),
+        count: None,
+        format: ExportFormat::Json,
+        columns: None,
+        filter: None,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        // Accept both "--flag value" and "--flag=value"
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next().cloned())
+                .ok_or_else(|| format!("Missing value for {}", flag))
+        };
+        match flag {
+            "--batch" => {}
+            "--interval" => {
+                let value = value()?;
+                let seconds: f64 = value
+                    .parse()
+                    .map_err(|_| format!("Invalid interval '{}'", value))?;
+                if !seconds.is_finite() || seconds <= 0.0 {
+                    return Err("Interval must be a positive number of seconds".to_string());
+                }
+                options.interval = Duration::try_from_secs_f64(seconds)
+                    .map_err(|_| format!("Interval '{}' is too large", value))?;
+            }
+            "--count" => {
+                let value = value()?;
+                let count: u64 = value.parse().map_err(|_| format!("Invalid count '{}'", value))?;
+                if count == 0 {
+                    return Err("Count must be at least 1".to_string());
+                }
+                options.count = Some(count);
+            }
+            "--format" => options.format = value()?.parse()?,
+            "--columns" => {
+                let columns: Vec<String> = value()?
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|column| !column.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                export::check_columns(&columns)?;
+                options.columns = Some(columns);
+            }
+            "--filter" => options.filter = Some(Filter::parse(&value()?)?),
+            _ => return Err(format!("Unknown argument '{}'", arg)),
+        }
+    }
+    Ok(options)
+}
+
+fn run(options: BatchOptions) -> Result<(), String> {
+    let state = AppState::new();
+    // CPU usage is measured between two refreshes, so give the first one time to mean something
+    std::thread::sleep(System::MINIMUM_CPU_UPDATE_INTERVAL);
+
+    let stdout = std::io::stdout();
+    let mut writer = BufWriter::new(stdout.lock());
+    let mut printed = 0;
+    loop {
+        let started = Instant::now();
+        let (mut processes, system_stats) = read_processes(&state)?;
+        if let Some(filter) = &options.filter {
+            processes.retain(|process| filter.matches(process));
+        }
+        let timestamp = unix_millis()?;
+        let system = serde_json::to_value(&system_stats).map_err(|e| e.to_string())?;
+        let (rows, columns) = export::select_rows(&processes, options.columns.clone())?;
+        match export::write_snapshot(
+            &mut writer,
+            options.format,
+            timestamp,
+            &system,
+            &rows,
+            &columns,
+            CsvLayout {
+                header: printed == 0,
+                timestamp_column: true,
+            },
+        ) {
+            Ok(()) => {}
+            // A closed pipe, e.g. `| head`, is a normal way for a batch run to end
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+            Err(e) => return Err(format!("Failed to write to stdout: {}", e)),
+        }
+
+        printed += 1;
+        if options.count.is_some_and(|count| printed >= count) {
+            return Ok(());
+        }
+        std::thread::sleep(options.interval.saturating_sub(started.elapsed()));
+    }
+}
+
+// Release builds use the windows subsystem and start without a console, so borrow the one of
+// the shell that launched us. Output that is redirected to a file or pipe works either way.
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // Fails harmlessly when there is already a console or no parent console to attach to
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// Runs batch mode and returns the process exit code.
+pub fn main(args: &[String]) -> i32 {
+    #[cfg(windows)]
+    attach_console();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return 0;
+    }
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+    match run(options) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<BatchOptions, String> {
+        parse_args(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn defaults_without_options() {
+        let options = parse(&["--batch"]).unwrap();
+        assert_eq!(options.interval, Duration::from_secs(2));
+        assert!(options.count.is_none() && options.columns.is_none() && options.filter.is_none());
+    }
+
+    #[test]
+    fn takes_values_after_a_space_or_equals_sign() {
+        let options = parse(&["--batch", "--interval", "0.5", "--count=3", "--filter", "pid == 1"]).unwrap();
+        assert_eq!(options.interval, Duration::from_millis(500));
+        assert_eq!(options.count, Some(3));
+        assert!(options.filter.is_some());
+        let options = parse(&["--interval=10", "--columns", "pid, name"]).unwrap();
+        assert_eq!(options.interval, Duration::from_secs(10));
+        assert_eq!(options.columns.unwrap(), ["pid", "name"]);
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        assert_eq!(parse(&["--interval", "soon"]).err().unwrap(), "Invalid interval 'soon'");
+        assert!(parse(&["--interval", "0"]).is_err());
+        assert!(parse(&["--interval", "-1"]).is_err());
+        assert!(parse(&["--interval", "NaN"]).is_err());
+        assert_eq!(parse(&["--interval", "1e300"]).err().unwrap(), "Interval '1e300' is too large");
+        assert_eq!(parse(&["--count", "0"]).err().unwrap(), "Count must be at least 1");
+        assert_eq!(parse(&["--count", "-2"]).err().unwrap(), "Invalid count '-2'");
+        assert!(parse(&["--filter", "pid =="]).is_err());
+        assert!(parse(&["--columns", "pid,bogus"]).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_and_incomplete_arguments() {
+        assert_eq!(parse(&["--batch", "--verbose"]).err().unwrap(), "Unknown argument '--verbose'");
+        assert_eq!(parse(&["--count"]).err().unwrap(), "Missing value for --count");
+        assert_eq!(parse(&["--filter"]).err().unwrap(), "Missing value for --filter");
+    }
+}
@@ -0,0 +1,275 @@
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SocketProtocol {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+    Unix,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SocketConnection {
+    pub fd: u32,
+    pub inode: u64,
+    pub protocol: SocketProtocol,
+    /// "ip:port" for inet sockets, the bound path (if any) for unix sockets
+    pub local_address: String,
+    pub remote_address: Option<String>,
+    pub state: String,
+    pub send_queue: Option<u64>,
+    pub receive_queue: Option<u64>,
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{SocketConnection, SocketProtocol};
+    use std::collections::HashMap;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+    const INET_TABLES: &[(&str, SocketProtocol)] = &[
+        ("tcp", SocketProtocol::Tcp),
+        ("tcp6", SocketProtocol::Tcp6),
+        ("udp", SocketProtocol::Udp),
+        ("udp6", SocketProtocol::Udp6),
+    ];
+
+    // Set in the unix table flags for sockets that called listen()
+    const SO_ACCEPTCON: u32 = 0x0001_0000;
+
+    fn tcp_state(code: u8) -> &'static str {
+        match code {
+            0x01 => "ESTABLISHED",
+            0x02 => "SYN_SENT",
+            0x03 => "SYN_RECV",
+            0x04 => "FIN_WAIT1",
+            0x05 => "FIN_WAIT2",
+            0x06 => "TIME_WAIT",
+            0x07 => "CLOSE",
+            0x08 => "CLOSE_WAIT",
+            0x09 => "LAST_ACK",
+            0x0A => "LISTEN",
+            0x0B => "CLOSING",
+            0x0C => "NEW_SYN_RECV",
+            _ => "UNKNOWN",
+        }
+    }
+
+    fn udp_state(code: u8) -> &'static str {
+        // UDP reuses the TCP codes, but only "connected" and "not" mean anything
+        match code {
+            0x01 => "CONNECTED",
+            0x07 => "UNCONNECTED",
+            _ => tcp_state(code),
+        }
+    }
+
+    fn unix_state(code: u8, flags: u32) -> &'static str {
+        if flags & SO_ACCEPTCON != 0 {
+            return "LISTEN";
+        }
+        match code {
+            0x01 => "UNCONNECTED",
+            0x02 => "CONNECTING",
+            0x03 => "CONNECTED",
+            0x04 => "DISCONNECTING",
+            _ => "UNKNOWN",
+        }
+    }
+
+    // The kernel prints each 32-bit word of the address in host byte order
+    fn parse_address(value: &str) -> Option<SocketAddr> {
+        let (ip, port) = value.split_once(':')?;
+        let port = u16::from_str_radix(port, 16).ok()?;
+        let words = (0..ip.len() / 8)
+            .map(|i| u32::from_str_radix(&ip[i * 8..i * 8 + 8], 16).map(u32::to_ne_bytes))
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+
+        let ip = match words.as_slice() {
+            [word] => IpAddr::V4(Ipv4Addr::from(*word)),
+            [a, b, c, d] => {
+                let mut bytes = [0u8; 16];
+                for (chunk, word) in bytes.chunks_mut(4).zip([a, b, c, d]) {
+                    chunk.copy_from_slice(word);
+                }
+                let v6 = Ipv6Addr::from(bytes);
+                // Show v4-mapped peers on dual-stack sockets as plain v4
+                v6.to_ipv4_mapped().map_or(IpAddr::V6(v6), IpAddr::V4)
+            }
+            _ => return None,
+        };
+        Some(SocketAddr::new(ip, port))
+    }
+
+    fn parse_queues(value: &str) -> (Option<u64>, Option<u64>) {
+        match value.split_once(':') {
+            Some((tx, rx)) => (
+                u64::from_str_radix(tx, 16).ok(),
+                u64::from_str_radix(rx, 16).ok(),
+            ),
+            None => (None, None),
+        }
+    }
+
+    // A dup'd socket is one inode behind several descriptors, so list it under each of them
+    fn push_for_each_fd(connections: &mut Vec<SocketConnection>, fds: &[u32], connection: SocketConnection) {
+        connections.extend(fds.iter().map(|fd| SocketConnection {
+            fd: *fd,
+            ..connection.clone()
+        }));
+    }
+
+    fn read_inet_table(
+        pid: u32,
+        table: &str,
+        protocol: SocketProtocol,
+        wanted: &HashMap<u64, Vec<u32>>,
+        connections: &mut Vec<SocketConnection>,
+    ) {
+        let Ok(contents) = std::fs::read_to_string(format!("/proc/{}/net/{}", pid, table)) else {
+            return;
+        };
+        for line in contents.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 {
+                continue;
+            }
+            let Some(inode) = fields[9].parse::<u64>().ok() else {
+                continue;
+            };
+            let Some(fds) = wanted.get(&inode) else {
+                continue;
+            };
+            let (Some(local), Some(remote)) = (parse_address(fields[1]), parse_address(fields[2]))
+            else {
+                continue;
+            };
+            let code = u8::from_str_radix(fields[3], 16).unwrap_or(0);
+            let state = match protocol {
+                SocketProtocol::Udp | SocketProtocol::Udp6 => udp_state(code),
+                _ => tcp_state(code),
+            };
+            let (send_queue, receive_queue) = parse_queues(fields[4]);
+
+            push_for_each_fd(connections, fds, SocketConnection {
+                fd: 0,
+                inode,
+                protocol,
+                local_address: local.to_string(),
+                // An all-zero peer means there is no remote end yet
+                remote_address: (remote.port() != 0 || !remote.ip().is_unspecified())
+                    .then(|| remote.to_string()),
+                state: state.to_string(),
+                send_queue,
+                receive_queue,
+            });
+        }
+    }
+
+    fn read_unix_table(pid: u32, wanted: &HashMap<u64, Vec<u32>>, connections: &mut Vec<SocketConnection>) {
+        let Ok(contents) = std::fs::read_to_string(format!("/proc/{}/net/unix", pid)) else {
+            return;
+        };
+        for line in contents.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 7 {
+                continue;
+            }
+            let Some(inode) = fields[6].parse::<u64>().ok() else {
+                continue;
+            };
+            let Some(fds) = wanted.get(&inode) else {
+                continue;
+            };
+            let flags = u32::from_str_radix(fields[3], 16).unwrap_or(0);
+            let code = u8::from_str_radix(fields[5], 16).unwrap_or(0);
+
+            push_for_each_fd(connections, fds, SocketConnection {
+                fd: 0,
+                inode,
+                protocol: SocketProtocol::Unix,
+                local_address: fields[7..].join(" "),
+                remote_address: None,
+                state: unix_state(code, flags).to_string(),
+                send_queue: None,
+                receive_queue: None,
+            });
+        }
+    }
+
+    pub fn list_connections(pid: u32) -> Result<Vec<SocketConnection>, String> {
+        let mut wanted: HashMap<u64, Vec<u32>> = HashMap::new();
+        for (fd, inode) in crate::fds::socket_inodes(pid)? {
+            wanted.entry(inode).or_default().push(fd);
+        }
+
+        let mut connections = Vec::new();
+        if wanted.is_empty() {
+            return Ok(connections);
+        }
+
+        // The per-process tables reflect the process's own network namespace
+        for (table, protocol) in INET_TABLES {
+            read_inet_table(pid, table, *protocol, &wanted, &mut connections);
+        }
+        read_unix_table(pid, &wanted, &mut connections);
+
+        connections.sort_by_key(|connection| connection.fd);
+        Ok(connections)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::net::TcpListener;
+        use std::os::fd::AsRawFd;
+
+        #[cfg(target_endian = "little")]
+        #[test]
+        fn parses_kernel_addresses() {
+            assert_eq!(parse_address("0100007F:1F90"), "127.0.0.1:8080".parse().ok());
+            assert_eq!(parse_address("00000000000000000000000001000000:0050"), "[::1]:80".parse().ok());
+            // v4-mapped peers on a dual-stack socket come out as plain v4
+            assert_eq!(parse_address("0000000000000000FFFF00000100007F:0016"), "127.0.0.1:22".parse().ok());
+            assert_eq!(parse_address("0100007F"), None);
+            assert_eq!(parse_address("0100:0016"), None);
+        }
+
+        #[test]
+        fn parses_queues_and_states() {
+            assert_eq!(parse_queues("0000001A:00000100"), (Some(0x1a), Some(0x100)));
+            assert_eq!(parse_queues("garbage"), (None, None));
+            assert_eq!(tcp_state(0x0A), "LISTEN");
+            assert_eq!(udp_state(0x07), "UNCONNECTED");
+            assert_eq!(unix_state(0x01, SO_ACCEPTCON), "LISTEN");
+        }
+
+        #[test]
+        fn lists_every_descriptor_of_a_dup_socket() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let copy = listener.try_clone().unwrap();
+            let connections = list_connections(std::process::id()).unwrap();
+            for fd in [listener.as_raw_fd(), copy.as_raw_fd()] {
+                let connection = connections.iter().find(|connection| connection.fd == fd as u32).unwrap();
+                assert_eq!(connection.state, "LISTEN");
+                assert_eq!(connection.local_address, listener.local_addr().unwrap().to_string());
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use linux::list_connections;
+
+#[cfg(not(target_os = "linux"))]
+pub fn list_connections(_pid: u32) -> Result<Vec<SocketConnection>, String> {
+    Err("Listing network connections is not supported on this platform".to_string())
+}
+
+#[tauri::command]
+pub async fn get_process_connections(pid: u32) -> Result<Vec<SocketConnection>, String> {
+    list_connections(pid)
+}
@@ -0,0 +1,384 @@
+//! Numbered process deltas per window. A window picks what it shows with watch_processes,
+//! after which every sample sends it only the added, changed and removed rows of its own
+//! page. Windows that watch nothing cost nothing.
+
+use crate::query::ProcessQuery;
+use crate::sampler::Snapshot;
+use crate::{current_snapshot, unix_millis, AppState, ProcessInfo, SystemStats};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, VecDeque};
+use tauri::{State, WebviewWindow};
+
+// Older bases than this get a full resync
+const MAX_RETAINED_FRAMES: usize = 4;
+
+struct Frame {
+    sequence: u64,
+    timestamp: u64,
+    processes: HashMap<u32, Map<String, Value>>,
+    order: Vec<u32>,
+    total: usize,
+    system: SystemStats,
+}
+
+/// Changes between two numbered snapshots of a page. Apply `removed`, then `added`, then `changed`.
+#[derive(Serialize, Clone)]
+pub struct ProcessDelta {
+    pub sequence: u64,
+    /// The snapshot this delta applies on top of; None means a full resync
+    pub base: Option<u64>,
+    pub timestamp: u64,
+    /// New processes in full, or every process on a full resync
+    pub added: Vec<Value>,
+    /// Only the fields that differ, plus the pid
+    pub changed: Vec<Map<String, Value>>,
+    pub removed: Vec<u32>,
+    /// PIDs on the page in display order. The selected process may be carried without being listed.
+    pub order: Vec<u32>,
+    /// Number of processes matching the query, before offset and limit
+    pub total: usize,
+    /// System stats are small enough to always send whole
+    pub system: SystemStats,
+}
+
+/// Numbers every snapshot of one page and keeps the last few to diff against.
+#[derive(Default)]
+pub struct DeltaEncoder {
+    frames: VecDeque<Frame>,
+    next_sequence: u64,
+}
+
+fn to_fields(process: &ProcessInfo) -> Result<Map<String, Value>, String> {
+    match serde_json::to_value(process).map_err(|e| e.to_string())? {
+        Value::Object(fields) => Ok(fields),
+        _ => Err("Process did not serialize to an object".to_string()),
+    }
+}
+
+fn diff(base: &Frame, latest: &Frame) -> (Vec<Value>, Vec<Map<String, Value>>, Vec<u32>) {
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for (pid, fields) in &latest.processes {
+        match base.processes.get(pid) {
+            // A different start time means the PID was reused, so send the new process whole
+            Some(old) if old.get("start_time") == fields.get("start_time") => {
+                let mut changes: Map<String, Value> = fields
+                    .iter()
+                    .filter(|(key, value)| old.get(*key) != Some(*value))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
+                if !changes.is_empty() {
+                    changes.insert("pid".to_string(), Value::from(*pid));
+                    changed.push(changes);
+                }
+            }
+            _ => added.push(Value::Object(fields.clone())),
+        }
+    }
+    let removed = base
+        .processes
+        .keys()
+        .filter(|pid| !latest.processes.contains_key(pid))
+        .copied()
+        .collect();
+    (added, changed, removed)
+}
+
+impl DeltaEncoder {
+    /// Records a new snapshot of the page and returns its delta against the previous one.
+    pub fn push(
+        &mut self,
+        timestamp: u64,
+        rows: &[&ProcessInfo],
+        order: Vec<u32>,
+        total: usize,
+        system: &SystemStats,
+    ) -> Result<ProcessDelta, String> {
+        let processes = rows
+            .iter()
+            .map(|process| Ok((process.pid, to_fields(process)?)))
+            .collect::<Result<_, String>>()?;
+        self.next_sequence += 1;
+        self.frames.push_back(Frame {
+            sequence: self.next_sequence,
+            timestamp,
+            processes,
+            order,
+            total,
+            system: system.clone(),
+        });
+        if self.frames.len() > MAX_RETAINED_FRAMES {
+            self.frames.pop_front();
+        }
+        self.delta_since(Some(self.next_sequence - 1))
+            .ok_or_else(|| "No snapshot available".to_string())
+    }
+
+    /// Returns the changes from `since` to the latest snapshot, or a full
+    /// resync when `since` is None or too old. None before the first snapshot.
+    pub fn delta_since(&self, since: Option<u64>) -> Option<ProcessDelta> {
+        let latest = self.frames.back()?;
+        let base = since.and_then(|since| self.frames.iter().find(|frame| frame.sequence == since));
+
+        let (added, changed, removed) = match base {
+            Some(base) => diff(base, latest),
+            None => (
+                latest
+                    .processes
+                    .values()
+                    .map(|fields| Value::Object(fields.clone()))
+                    .collect(),
+                Vec::new(),
+                Vec::new(),
+            ),
+        };
+        Some(ProcessDelta {
+            sequence: latest.sequence,
+            base: base.map(|base| base.sequence),
+            timestamp: latest.timestamp,
+            added,
+            changed,
+            removed,
+            order: latest.order.clone(),
+            total: latest.total,
+            system: latest.system.clone(),
+        })
+    }
+}
+
+struct View {
+    query: ProcessQuery,
+    /// Carried for the details panel even when it is not on the page
+    selected: Option<u32>,
+    encoder: DeltaEncoder,
+}
+
+impl View {
+    fn push(&mut self, timestamp: u64, snapshot: &Snapshot) -> Result<ProcessDelta, String> {
+        let (mut rows, total) = self.query.select(&snapshot.0)?;
+        let order: Vec<u32> = rows.iter().map(|process| process.pid).collect();
+        if let Some(pid) = self.selected.filter(|pid| !order.contains(pid)) {
+            rows.extend(snapshot.0.iter().find(|process| process.pid == pid));
+        }
+        self.encoder.push(timestamp, &rows, order, total, &snapshot.1)
+    }
+}
+
+/// What each window is watching, by window label.
+#[derive(Default)]
+pub struct ProcessViews {
+    views: HashMap<String, View>,
+}
+
+impl ProcessViews {
+    /// Replaces the window's view and returns its page in full. A filter that does not
+    /// parse leaves the previous view in place.
+    pub fn watch(
+        &mut self,
+        label: &str,
+        query: ProcessQuery,
+        selected: Option<u32>,
+        timestamp: u64,
+        snapshot: &Snapshot,
+    ) -> Result<ProcessDelta, String> {
+        let mut view = View {
+            query,
+            selected,
+            encoder: DeltaEncoder::default(),
+        };
+        // Keep numbering so deltas of the old view still in flight are recognisably older
+        if let Some(old) = self.views.get(label) {
+            view.encoder.next_sequence = old.encoder.next_sequence;
+        }
+        let delta = view.push(timestamp, snapshot)?;
+        self.views.insert(label.to_string(), view);
+        Ok(delta)
+    }
+
+    pub fn unwatch(&mut self, label: &str) {
+        self.views.remove(label);
+    }
+
+    pub fn delta_since(&self, label: &str, since: Option<u64>) -> Option<ProcessDelta> {
+        self.views.get(label)?.encoder.delta_since(since)
+    }
+
+    /// Forgets views whose window is gone.
+    pub fn retain(&mut self, mut open: impl FnMut(&str) -> bool) {
+        self.views.retain(|label, _| open(label));
+    }
+
+    /// Steps every view with a new snapshot and returns each window's delta.
+    pub fn push(&mut self, timestamp: u64, snapshot: &Snapshot) -> Vec<(String, Result<ProcessDelta, String>)> {
+        self.views
+            .iter_mut()
+            .map(|(label, view)| (label.clone(), view.push(timestamp, snapshot)))
+            .collect()
+    }
+}
+
+/// Makes this window watch `query`, plus `selected` wherever it sorts, and returns the page
+/// in full. Every later sample sends the window a sampler::DELTA_EVENT.
+#[tauri::command]
+pub async fn watch_processes(
+    query: ProcessQuery,
+    selected: Option<u32>,
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+) -> Result<ProcessDelta, String> {
+    let snapshot = current_snapshot(&state)?;
+    let timestamp = unix_millis()?;
+    state
+        .views
+        .lock()
+        .map_err(|_| "Failed to lock process views")?
+        .watch(window.label(), query, selected, timestamp, &snapshot)
+}
+
+#[tauri::command]
+pub async fn unwatch_processes(window: WebviewWindow, state: State<'_, AppState>) -> Result<(), String> {
+    state
+        .views
+        .lock()
+        .map_err(|_| "Failed to lock process views")?
+        .unwatch(window.label());
+    Ok(())
+}
+
+/// Returns what changed on this window's page since snapshot `since`, the last one it
+/// applied. Pass no `since` to request a full resync.
+#[tauri::command]
+pub async fn get_process_delta(
+    since: Option<u64>,
+    window: WebviewWindow,
+    state: State<'_, AppState>,
+) -> Result<ProcessDelta, String> {
+    state
+        .views
+        .lock()
+        .map_err(|_| "Failed to lock process views")?
+        .delta_since(window.label(), since)
+        .ok_or_else(|| "This window is not watching any processes".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::SortKey;
+
+    fn process(pid: u32, start_time: u64, cpu_usage: f32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            start_time,
+            cpu_usage,
+            name: format!("p{}", pid),
+            ..ProcessInfo::default()
+        }
+    }
+
+    fn system() -> SystemStats {
+        serde_json::from_value(serde_json::json!({
+            "cpu_usage": [], "memory_total": 0, "memory_used": 0, "memory_free": 0,
+            "memory_cached": 0, "uptime": 0, "load_avg": [0.0, 0.0, 0.0],
+            "network_rx_bytes": 0, "network_tx_bytes": 0,
+            "disk_total_bytes": 0, "disk_used_bytes": 0, "disk_free_bytes": 0
+        }))
+        .unwrap()
+    }
+
+    fn push(encoder: &mut DeltaEncoder, processes: &[ProcessInfo]) -> ProcessDelta {
+        let rows: Vec<&ProcessInfo> = processes.iter().collect();
+        let order = processes.iter().map(|process| process.pid).collect();
+        encoder.push(0, &rows, order, processes.len(), &system()).unwrap()
+    }
+
+    fn pids(values: &[Value]) -> Vec<u64> {
+        let mut pids: Vec<u64> = values.iter().map(|value| value["pid"].as_u64().unwrap()).collect();
+        pids.sort();
+        pids
+    }
+
+    #[test]
+    fn sends_added_changed_and_removed_rows() {
+        let mut encoder = DeltaEncoder::default();
+        let first = push(&mut encoder, &[process(1, 10, 1.0), process(2, 10, 1.0)]);
+        assert_eq!((first.sequence, first.base), (1, None));
+        assert_eq!(pids(&first.added), [1, 2]);
+
+        let second = push(&mut encoder, &[process(1, 10, 5.0), process(3, 10, 1.0)]);
+        assert_eq!((second.sequence, second.base), (2, Some(1)));
+        assert_eq!(pids(&second.added), [3]);
+        assert_eq!(second.removed, [2]);
+        assert_eq!(second.changed.len(), 1);
+        let change = &second.changed[0];
+        assert_eq!(change.keys().collect::<Vec<_>>(), ["cpu_usage", "pid"]);
+        assert_eq!(change["cpu_usage"], 5.0);
+
+        let unchanged = push(&mut encoder, &[process(1, 10, 5.0), process(3, 10, 1.0)]);
+        assert!(unchanged.added.is_empty() && unchanged.changed.is_empty() && unchanged.removed.is_empty());
+    }
+
+    #[test]
+    fn reused_pid_is_sent_whole() {
+        let mut encoder = DeltaEncoder::default();
+        push(&mut encoder, &[process(1, 10, 1.0)]);
+        let delta = push(&mut encoder, &[process(1, 20, 1.0)]);
+        assert!(delta.changed.is_empty() && delta.removed.is_empty());
+        assert_eq!(delta.added.len(), 1);
+        assert_eq!(delta.added[0]["start_time"], 20);
+    }
+
+    #[test]
+    fn resyncs_after_a_gap() {
+        let mut encoder = DeltaEncoder::default();
+        assert!(encoder.delta_since(None).is_none());
+        for cpu_usage in 0..6 {
+            push(&mut encoder, &[process(1, 10, cpu_usage as f32)]);
+        }
+        // A client two samples behind still gets a delta
+        let behind = encoder.delta_since(Some(4)).unwrap();
+        assert_eq!((behind.sequence, behind.base), (6, Some(4)));
+        assert_eq!(behind.changed.len(), 1);
+        // One whose base was dropped, or who asks for it, gets everything
+        for since in [Some(1), None] {
+            let resync = encoder.delta_since(since).unwrap();
+            assert_eq!((resync.sequence, resync.base), (6, None));
+            assert_eq!(pids(&resync.added), [1]);
+        }
+    }
+
+    #[test]
+    fn views_page_their_query_and_carry_the_selection() {
+        let snapshot: Snapshot = (
+            vec![process(1, 10, 1.0), process(2, 10, 3.0), process(3, 10, 2.0)],
+            system(),
+        );
+        let query = ProcessQuery {
+            sort_by: Some(SortKey::CpuUsage),
+            limit: Some(2),
+            ..ProcessQuery::default()
+        };
+        let mut views = ProcessViews::default();
+        let delta = views.watch("main", query.clone(), Some(1), 0, &snapshot).unwrap();
+        assert_eq!((delta.order, delta.total), (vec![2, 3], 3));
+        assert_eq!(pids(&delta.added), [1, 2, 3]);
+
+        // A new query restarts the page but keeps counting
+        let delta = views.watch("main", query, None, 0, &snapshot).unwrap();
+        assert_eq!((delta.sequence, delta.base), (2, None));
+        assert_eq!(pids(&delta.added), [2, 3]);
+
+        let bad = ProcessQuery {
+            filter: Some("bogus ==".to_string()),
+            ..ProcessQuery::default()
+        };
+        assert!(views.watch("main", bad, None, 0, &snapshot).is_err());
+        let deltas = views.push(0, &snapshot);
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].1.as_ref().unwrap().base, Some(2));
+
+        views.retain(|label| label != "main");
+        assert!(views.push(0, &snapshot).is_empty());
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{current_snapshot, AppState, ProcessInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+use tauri::State;
+
+const DEFAULT_TOP_IO_LIMIT: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DiskIo {
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    /// Bytes read from and written to storage over the lifetime of the process
+    pub total_read_bytes: u64,
+    pub total_written_bytes: u64,
+    // The remaining counters come from /proc/<pid>/io and are Linux only
+    /// Bytes passed to read-like syscalls, including page cache hits
+    pub read_chars: Option<u64>,
+    pub written_chars: Option<u64>,
+    pub read_syscalls: Option<u64>,
+    pub write_syscalls: Option<u64>,
+    /// Bytes that were dirtied but never written back, e.g. truncated files
+    pub cancelled_write_bytes: Option<u64>,
+}
+
+impl DiskIo {
+    pub fn total_rate(&self) -> f64 {
+        self.read_bytes_per_sec + self.write_bytes_per_sec
+    }
+}
+
+struct IoSample {
+    start_time: u64,
+    taken_at: Instant,
+    total_read_bytes: u64,
+    total_written_bytes: u64,
+}
+
+/// Remembers the last lifetime totals per process so rates no longer
+/// depend on how often the frontend happens to refresh.
+#[derive(Default)]
+pub struct IoRateTracker {
+    samples: HashMap<u32, IoSample>,
+}
+
+impl IoRateTracker {
+    /// Returns (read, write) bytes per second since the previous sample of the same process.
+    pub fn update(
+        &mut self,
+        pid: u32,
+        start_time: u64,
+        taken_at: Instant,
+        total_read_bytes: u64,
+        total_written_bytes: u64,
+    ) -> (f64, f64) {
+        let sample = IoSample {
+            start_time,
+            taken_at,
+            total_read_bytes,
+            total_written_bytes,
+        };
+        let Some(previous) = self.samples.insert(pid, sample) else {
+            return (0.0, 0.0);
+        };
+        // A different start time means the PID was reused by a new process
+        if previous.start_time != start_time {
+            return (0.0, 0.0);
+        }
+        let elapsed = taken_at.duration_since(previous.taken_at).as_secs_f64();
+        if elapsed <= 0.0 {
+            return (0.0, 0.0);
+        }
+        (
+            total_read_bytes.saturating_sub(previous.total_read_bytes) as f64 / elapsed,
+            total_written_bytes.saturating_sub(previous.total_written_bytes) as f64 / elapsed,
+        )
+    }
+
+    /// Drops samples for processes that are no longer running.
+    pub fn retain_pids(&mut self, live: &HashSet<u32>) {
+        self.samples.retain(|pid, _| live.contains(pid));
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ProcIo {
+    pub rchar: Option<u64>,
+    pub wchar: Option<u64>,
+    pub syscr: Option<u64>,
+    pub syscw: Option<u64>,
+    pub cancelled_write_bytes: Option<u64>,
+}
+
+/// Reads /proc/<pid>/io, which is only readable for our own processes unless we are root.
+pub fn read_proc_io(pid: u32) -> ProcIo {
+    let mut io = ProcIo::default();
+    if !cfg!(target_os = "linux") {
+        return io;
+    }
+    let Ok(contents) = std::fs::read_to_string(format!("/proc/{}/io", pid)) else {
+        return io;
+    };
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().parse().ok();
+        match key {
+            "rchar" => io.rchar = value,
+            "wchar" => io.wchar = value,
+            "syscr" => io.syscr = value,
+            "syscw" => io.syscw = value,
+            "cancelled_write_bytes" => io.cancelled_write_bytes = value,
+            _ => {}
+        }
+    }
+    io
+}
+
+pub fn build_disk_io(
+    rates: (f64, f64),
+    total_read_bytes: u64,
+    total_written_bytes: u64,
+    proc_io: ProcIo,
+) -> DiskIo {
+    DiskIo {
+        read_bytes_per_sec: rates.0,
+        write_bytes_per_sec: rates.1,
+        total_read_bytes,
+        total_written_bytes,
+        read_chars: proc_io.rchar,
+        written_chars: proc_io.wchar,
+        read_syscalls: proc_io.syscr,
+        write_syscalls: proc_io.syscw,
+        cancelled_write_bytes: proc_io.cancelled_write_bytes,
+    }
+}
+
+/// Orders processes by current I/O rate, busiest first, falling back to lifetime totals.
+pub fn sort_by_io(processes: &mut [&ProcessInfo]) {
+    processes.sort_by(|a, b| {
+        b.disk_io
+            .total_rate()
+            .total_cmp(&a.disk_io.total_rate())
+            .then_with(|| {
+                let a_total = a.disk_io.total_read_bytes + a.disk_io.total_written_bytes;
+                let b_total = b.disk_io.total_read_bytes + b.disk_io.total_written_bytes;
+                b_total.cmp(&a_total)
+            })
+    });
+}
+
+// Copies only the `limit` busiest processes
+fn top_io(processes: &[ProcessInfo], limit: usize) -> Vec<ProcessInfo> {
+    let mut processes: Vec<&ProcessInfo> = processes.iter().collect();
+    sort_by_io(&mut processes);
+    processes.into_iter().take(limit).cloned().collect()
+}
+
+/// Ranks the current snapshot, so rates cover one regular sampling interval.
+#[tauri::command]
+pub async fn get_top_io_processes(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ProcessInfo>, String> {
+    let snapshot = current_snapshot(&state)?;
+    Ok(top_io(&snapshot.0, limit.unwrap_or(DEFAULT_TOP_IO_LIMIT)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn rates_need_an_earlier_sample_of_the_same_process() {
+        let mut tracker = IoRateTracker::default();
+        let start = Instant::now();
+        let later = start + Duration::from_secs(2);
+        assert_eq!(tracker.update(7, 100, start, 1_000, 500), (0.0, 0.0));
+        assert_eq!(tracker.update(7, 100, later, 3_000, 1_500), (1_000.0, 500.0));
+
+        // A new process with the reused PID starts over
+        let reused = later + Duration::from_secs(1);
+        assert_eq!(tracker.update(7, 200, reused, 10, 10), (0.0, 0.0));
+        let after = reused + Duration::from_secs(1);
+        assert_eq!(tracker.update(7, 200, after, 110, 10), (100.0, 0.0));
+    }
+
+    #[test]
+    fn rates_never_go_negative_or_divide_by_zero() {
+        let mut tracker = IoRateTracker::default();
+        let start = Instant::now();
+        tracker.update(7, 100, start, 1_000, 1_000);
+        // A PID reused within the same second keeps its start time but its counters restart
+        let later = start + Duration::from_secs(1);
+        assert_eq!(tracker.update(7, 100, later, 10, 2_000), (0.0, 1_000.0));
+        assert_eq!(tracker.update(7, 100, later, 500, 3_000), (0.0, 0.0));
+
+        tracker.retain_pids(&HashSet::new());
+        assert_eq!(tracker.update(7, 100, later + Duration::from_secs(1), 900, 3_000), (0.0, 0.0));
+    }
+
+    fn process(pid: u32, rate: f64, total: u64) -> ProcessInfo {
+        let mut process = ProcessInfo {
+            pid,
+            ..ProcessInfo::default()
+        };
+        process.disk_io.read_bytes_per_sec = rate;
+        process.disk_io.total_written_bytes = total;
+        process
+    }
+
+    #[test]
+    fn top_io_ranks_by_rate_then_total() {
+        let processes = [
+            process(1, 0.0, 50),
+            process(2, 10.0, 0),
+            process(3, 0.0, 900),
+            process(4, 300.0, 0),
+        ];
+        let pids = |limit| top_io(&processes, limit).iter().map(|p| p.pid).collect::<Vec<_>>();
+        assert_eq!(pids(10), [4, 2, 3, 1]);
+        assert_eq!(pids(2), [4, 2]);
+        assert!(pids(0).is_empty());
+    }
+}
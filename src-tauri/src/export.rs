@@ -0,0 +1,246 @@
+use crate::{current_snapshot, unix_millis, AppState, ProcessInfo};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+use tauri::State;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Ndjson,
+    Csv,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(Self::Json),
+            "ndjson" => Ok(Self::Ndjson),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!("Unknown format '{}', expected json, ndjson or csv", value)),
+        }
+    }
+}
+
+/// Serialized processes reduced to the selected columns
+pub type Rows = Vec<Map<String, Value>>;
+
+#[derive(Serialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub process_count: usize,
+    pub columns: Vec<String>,
+}
+
+// Keeps only the requested ProcessInfo fields; CSV columns follow the requested order
+fn select_columns(process: Value, columns: &[String]) -> Map<String, Value> {
+    let mut fields = match process {
+        Value::Object(fields) => fields,
+        _ => Map::new(),
+    };
+    columns
+        .iter()
+        .map(|column| (column.clone(), fields.remove(column).unwrap_or(Value::Null)))
+        .collect()
+}
+
+fn csv_field(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        // Nested values such as environ or disk_usage are kept as JSON
+        other => other.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) || text.starts_with(' ') || text.ends_with(' ') {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// How a CSV snapshot is laid out; the JSON formats ignore it.
+#[derive(Debug, Clone, Copy)]
+pub struct CsvLayout {
+    /// Lets a stream of CSV snapshots share a single header row
+    pub header: bool,
+    /// Leads every row with the snapshot timestamp to tell consecutive snapshots apart
+    pub timestamp_column: bool,
+}
+
+/// Writes one snapshot.
+pub fn write_snapshot(
+    writer: &mut impl Write,
+    format: ExportFormat,
+    timestamp: u64,
+    system: &Value,
+    rows: &[Map<String, Value>],
+    columns: &[String],
+    csv: CsvLayout,
+) -> std::io::Result<()> {
+    match format {
+        ExportFormat::Json => {
+            let document = serde_json::json!({
+                "timestamp": timestamp,
+                "system": system,
+                "processes": rows,
+            });
+            serde_json::to_writer_pretty(&mut *writer, &document)?;
+            writeln!(writer)?;
+        }
+        ExportFormat::Ndjson => {
+            // The first line describes the system, every following line is one process
+            let header = serde_json::json!({ "timestamp": timestamp, "system": system });
+            writeln!(writer, "{}", header)?;
+            for row in rows {
+                writeln!(writer, "{}", Value::Object(row.clone()))?;
+            }
+        }
+        ExportFormat::Csv => {
+            // CSV has no room for the system stats, so it only holds the process table
+            let timestamp_column = csv.timestamp_column.then_some("timestamp");
+            if csv.header {
+                let header: Vec<String> = timestamp_column
+                    .into_iter()
+                    .chain(columns.iter().map(String::as_str))
+                    .map(|column| csv_field(&Value::String(column.to_string())))
+                    .collect();
+                writeln!(writer, "{}", header.join(","))?;
+            }
+            for row in rows {
+                let line: Vec<String> = timestamp_column
+                    .map(|_| timestamp.to_string())
+                    .into_iter()
+                    .chain(
+                        columns
+                            .iter()
+                            .map(|column| csv_field(row.get(column).unwrap_or(&Value::Null))),
+                    )
+                    .collect();
+                writeln!(writer, "{}", line.join(","))?;
+            }
+        }
+    }
+    writer.flush()
+}
+
+/// Names of every ProcessInfo field, i.e. every valid column
+pub fn known_columns() -> Vec<String> {
+    match serde_json::to_value(ProcessInfo::default()) {
+        Ok(Value::Object(fields)) => fields.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Fails on the first column that is not a ProcessInfo field.
+pub fn check_columns(columns: &[String]) -> Result<(), String> {
+    let known = known_columns();
+    match columns.iter().find(|column| !known.contains(column)) {
+        Some(unknown) => Err(format!(
+            "Unknown column '{}', expected one of: {}",
+            unknown,
+            known.join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Serializes each process and keeps the requested columns, all of them by default.
+pub fn select_rows(
+    processes: &[ProcessInfo],
+    columns: Option<Vec<String>>,
+) -> Result<(Rows, Vec<String>), String> {
+    let processes: Vec<Value> = processes
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    // Taken from the type rather than the rows, so an empty snapshot keeps its columns
+    let columns = match columns {
+        Some(columns) if !columns.is_empty() => {
+            check_columns(&columns)?;
+            columns
+        }
+        _ => known_columns(),
+    };
+
+    let rows: Rows = processes
+        .into_iter()
+        .map(|process| select_columns(process, &columns))
+        .collect();
+
+    Ok((rows, columns))
+}
+
+/// Writes the current process list and system stats to `path`. `columns`
+/// takes ProcessInfo field names and defaults to all of them.
+#[tauri::command]
+pub async fn export_snapshot(
+    path: String,
+    format: ExportFormat,
+    columns: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<ExportSummary, String> {
+    let snapshot = current_snapshot(&state)?;
+    let timestamp = unix_millis()?;
+
+    let system = serde_json::to_value(&snapshot.1).map_err(|e| e.to_string())?;
+    let (rows, columns) = select_rows(&snapshot.0, columns)?;
+
+    let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    let mut writer = BufWriter::new(file);
+    let csv = CsvLayout {
+        header: true,
+        timestamp_column: false,
+    };
+    write_snapshot(&mut writer, format, timestamp, &system, &rows, &columns, csv)
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    Ok(ExportSummary {
+        path,
+        process_count: rows.len(),
+        columns,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv(rows: &[Map<String, Value>], columns: &[String], csv: CsvLayout) -> String {
+        let mut out = Vec::new();
+        let system = Value::Null;
+        write_snapshot(&mut out, ExportFormat::Csv, 42, &system, rows, columns, csv).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn empty_snapshot_keeps_every_column() {
+        let (rows, columns) = select_rows(&[], None).unwrap();
+        assert!(rows.is_empty());
+        assert!(columns.contains(&"pid".to_string()) && columns.contains(&"credentials".to_string()));
+    }
+
+    #[test]
+    fn rejects_unknown_columns() {
+        let err = select_rows(&[], Some(vec!["pid".into(), "nope".into()])).unwrap_err();
+        assert!(err.starts_with("Unknown column 'nope'"), "{}", err);
+    }
+
+    #[test]
+    fn timestamp_column_is_opt_in() {
+        let columns = vec!["pid".to_string(), "name".to_string()];
+        let row: Map<String, Value> =
+            serde_json::from_str(r#"{"pid": 7, "name": "a, b"}"#).unwrap();
+        let rows = [row];
+        let plain = CsvLayout { header: true, timestamp_column: false };
+        assert_eq!(csv(&rows, &columns, plain), "pid,name\n7,\"a, b\"\n");
+        let stamped = CsvLayout { header: false, timestamp_column: true };
+        assert_eq!(csv(&rows, &columns, stamped), "42,7,\"a, b\"\n");
+    }
+}
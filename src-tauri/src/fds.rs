@@ -0,0 +1,292 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FdKind {
+    RegularFile,
+    Directory,
+    CharDevice,
+    BlockDevice,
+    Pipe,
+    Socket,
+    EventFd,
+    EventPoll,
+    Inotify,
+    SignalFd,
+    TimerFd,
+    PidFd,
+    Memfd,
+    AnonInode,
+    Unknown,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FileDescriptor {
+    pub fd: u32,
+    pub kind: FdKind,
+    /// Link target from /proc/<pid>/fd, e.g. a path or "socket:[12345]"
+    pub target: String,
+    pub deleted: bool,
+    /// Inode for sockets and pipes, which is how peers can be matched up
+    pub inode: Option<u64>,
+    pub access_mode: Option<String>,
+    pub flags: Vec<String>,
+    pub position: Option<u64>,
+}
+
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct FdSummary {
+    pub total: u32,
+    pub by_kind: BTreeMap<FdKind, u32>,
+}
+
+impl FdSummary {
+    pub fn from_descriptors(descriptors: &[FileDescriptor]) -> Self {
+        let mut summary = FdSummary::default();
+        for descriptor in descriptors {
+            summary.total += 1;
+            *summary.by_kind.entry(descriptor.kind).or_insert(0) += 1;
+        }
+        summary
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct OpenFiles {
+    pub pid: u32,
+    pub descriptors: Vec<FileDescriptor>,
+    pub summary: FdSummary,
+}
+
+// Extracts the inode from link targets like "socket:[12345]"
+pub fn bracketed_inode(target: &str, prefix: &str) -> Option<u64> {
+    target
+        .strip_prefix(prefix)?
+        .strip_prefix(":[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{bracketed_inode, FdKind, FileDescriptor};
+    use std::fs;
+    use std::os::unix::fs::FileTypeExt;
+
+    // libc defines O_LARGEFILE as 0 on 64-bit targets, yet the kernel still sets
+    // its own bit on every open there, so fdinfo needs the raw per-arch value
+    #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+    const KERNEL_O_LARGEFILE: u32 = 0o400000;
+    #[cfg(any(target_arch = "powerpc", target_arch = "powerpc64"))]
+    const KERNEL_O_LARGEFILE: u32 = 0o200000;
+    #[cfg(not(any(
+        target_arch = "arm",
+        target_arch = "aarch64",
+        target_arch = "powerpc",
+        target_arch = "powerpc64"
+    )))]
+    const KERNEL_O_LARGEFILE: u32 = 0o100000;
+
+    // Flag bits as they appear (in octal) in /proc/<pid>/fdinfo
+    const OPEN_FLAGS: &[(u32, &str)] = &[
+        (libc::O_CREAT as u32, "O_CREAT"),
+        (libc::O_EXCL as u32, "O_EXCL"),
+        (libc::O_NOCTTY as u32, "O_NOCTTY"),
+        (libc::O_TRUNC as u32, "O_TRUNC"),
+        (libc::O_APPEND as u32, "O_APPEND"),
+        (libc::O_NONBLOCK as u32, "O_NONBLOCK"),
+        (libc::O_DSYNC as u32, "O_DSYNC"),
+        (libc::O_DIRECT as u32, "O_DIRECT"),
+        (KERNEL_O_LARGEFILE, "O_LARGEFILE"),
+        (libc::O_DIRECTORY as u32, "O_DIRECTORY"),
+        (libc::O_NOFOLLOW as u32, "O_NOFOLLOW"),
+        (libc::O_NOATIME as u32, "O_NOATIME"),
+        (libc::O_CLOEXEC as u32, "O_CLOEXEC"),
+        (libc::O_PATH as u32, "O_PATH"),
+        (libc::O_TMPFILE as u32 & !(libc::O_DIRECTORY as u32), "O_TMPFILE"),
+    ];
+
+    fn classify(pid: u32, fd: u32, target: &str) -> FdKind {
+        if target.starts_with("socket:") {
+            return FdKind::Socket;
+        }
+        if target.starts_with("pipe:") {
+            return FdKind::Pipe;
+        }
+        if let Some(anon) = target.strip_prefix("anon_inode:") {
+            return match anon.trim_matches(|c| c == '[' || c == ']') {
+                "eventfd" => FdKind::EventFd,
+                "eventpoll" => FdKind::EventPoll,
+                "inotify" => FdKind::Inotify,
+                "signalfd" => FdKind::SignalFd,
+                "timerfd" => FdKind::TimerFd,
+                "pidfd" => FdKind::PidFd,
+                _ => FdKind::AnonInode,
+            };
+        }
+        if target.starts_with("/memfd:") {
+            return FdKind::Memfd;
+        }
+
+        // Following the fd link itself still works for deleted files
+        match fs::metadata(format!("/proc/{}/fd/{}", pid, fd)) {
+            Ok(metadata) => {
+                let file_type = metadata.file_type();
+                if file_type.is_dir() {
+                    FdKind::Directory
+                } else if file_type.is_file() {
+                    FdKind::RegularFile
+                } else if file_type.is_char_device() {
+                    FdKind::CharDevice
+                } else if file_type.is_block_device() {
+                    FdKind::BlockDevice
+                } else if file_type.is_fifo() {
+                    FdKind::Pipe
+                } else if file_type.is_socket() {
+                    FdKind::Socket
+                } else {
+                    FdKind::Unknown
+                }
+            }
+            Err(_) => FdKind::Unknown,
+        }
+    }
+
+    fn decode_flags(raw: u32) -> (String, Vec<String>) {
+        let access_mode = match raw & libc::O_ACCMODE as u32 {
+            x if x == libc::O_RDONLY as u32 => "read",
+            x if x == libc::O_WRONLY as u32 => "write",
+            _ => "read_write",
+        };
+        let flags = OPEN_FLAGS
+            .iter()
+            // A flag that is 0 on this target must not always match
+            .filter(|(bit, _)| *bit != 0 && raw & bit == *bit)
+            .map(|(_, name)| name.to_string())
+            .collect();
+        (access_mode.to_string(), flags)
+    }
+
+    fn read_fdinfo(pid: u32, fd: u32) -> (Option<u64>, Option<u32>) {
+        let Ok(fdinfo) = fs::read_to_string(format!("/proc/{}/fdinfo/{}", pid, fd)) else {
+            return (None, None);
+        };
+        let mut position = None;
+        let mut flags = None;
+        for line in fdinfo.lines() {
+            if let Some(value) = line.strip_prefix("pos:") {
+                position = value.trim().parse().ok();
+            } else if let Some(value) = line.strip_prefix("flags:") {
+                flags = u32::from_str_radix(value.trim(), 8).ok();
+            }
+        }
+        (position, flags)
+    }
+
+    // Yields (fd, link target) for every descriptor still open while we look
+    fn read_fd_links(pid: u32) -> Result<impl Iterator<Item = (u32, String)>, String> {
+        let entries = fs::read_dir(format!("/proc/{}/fd", pid)).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => format!("No such process: {}", pid),
+            std::io::ErrorKind::PermissionDenied => format!(
+                "Permission denied: inspecting the files of process {} requires elevated privileges",
+                pid
+            ),
+            _ => format!("Failed to list the files of process {}: {}", pid, e),
+        })?;
+
+        Ok(entries.filter_map(|entry| {
+            let entry = entry.ok()?;
+            let fd: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let target = fs::read_link(entry.path()).ok()?.to_string_lossy().into_owned();
+            Some((fd, target))
+        }))
+    }
+
+    pub fn socket_inodes(pid: u32) -> Result<Vec<(u32, u64)>, String> {
+        Ok(read_fd_links(pid)?
+            .filter_map(|(fd, target)| Some((fd, bracketed_inode(&target, "socket")?)))
+            .collect())
+    }
+
+    pub fn list_descriptors(pid: u32) -> Result<Vec<FileDescriptor>, String> {
+        let mut descriptors: Vec<FileDescriptor> = read_fd_links(pid)?
+            .map(|(fd, target)| {
+                let kind = classify(pid, fd, &target);
+                let (position, raw_flags) = read_fdinfo(pid, fd);
+                let (access_mode, flags) = match raw_flags {
+                    Some(raw) => {
+                        let (mode, flags) = decode_flags(raw);
+                        (Some(mode), flags)
+                    }
+                    None => (None, Vec::new()),
+                };
+
+                FileDescriptor {
+                    fd,
+                    kind,
+                    deleted: target.ends_with(" (deleted)"),
+                    inode: bracketed_inode(&target, "socket")
+                        .or_else(|| bracketed_inode(&target, "pipe")),
+                    target,
+                    access_mode,
+                    flags,
+                    position,
+                }
+            })
+            .collect();
+
+        descriptors.sort_by_key(|descriptor| descriptor.fd);
+        Ok(descriptors)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::os::fd::AsRawFd;
+
+        #[test]
+        fn decodes_access_mode_and_flags() {
+            let raw = libc::O_WRONLY as u32 | libc::O_APPEND as u32 | libc::O_CLOEXEC as u32;
+            assert_eq!(
+                decode_flags(raw),
+                ("write".to_string(), vec!["O_APPEND".to_string(), "O_CLOEXEC".to_string()])
+            );
+        }
+
+        #[cfg(target_pointer_width = "64")]
+        #[test]
+        fn reports_the_largefile_bit_the_kernel_sets() {
+            let file = fs::File::open("/proc/self/stat").unwrap();
+            let fd = file.as_raw_fd() as u32;
+            let descriptor = list_descriptors(std::process::id())
+                .unwrap()
+                .into_iter()
+                .find(|descriptor| descriptor.fd == fd)
+                .unwrap();
+            assert_eq!(descriptor.access_mode.as_deref(), Some("read"));
+            assert!(descriptor.flags.iter().any(|flag| flag == "O_LARGEFILE"));
+            assert!(descriptor.flags.iter().any(|flag| flag == "O_CLOEXEC"));
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use linux::{list_descriptors, socket_inodes};
+
+#[cfg(not(target_os = "linux"))]
+pub fn list_descriptors(_pid: u32) -> Result<Vec<FileDescriptor>, String> {
+    Err("Inspecting open files is not supported on this platform".to_string())
+}
+
+#[tauri::command]
+pub async fn get_open_files(pid: u32) -> Result<OpenFiles, String> {
+    let descriptors = list_descriptors(pid)?;
+    let summary = FdSummary::from_descriptors(&descriptors);
+    Ok(OpenFiles {
+        pid,
+        descriptors,
+        summary,
+    })
+}
@@ -0,0 +1,687 @@
+//! A small filter language over ProcessInfo fields, e.g.
+//!
+//! ```text
+//! cpu_usage > 10 and user == "postgres" and command ~ /--replica/
+//! not (status == "Sleeping" or memory_usage < 500MB)
+//! ```
+//!
+//! Numeric fields support `== != > >= < <=` against numbers, which may carry a
+//! size unit (B, KB, MB, GB, TB; 1024 based) or a trailing `%`. Text fields
+//! support `==` and `!=` against quoted strings and `~` / `!~` against a
+//! `/regex/` (add `i` after the closing slash to ignore case) or a string.
+//! `and`, `or` and `not` may also be written `&&`, `||` and `!`. A field
+//! with no value for a process, e.g. `nice` on macOS, matches no comparison.
+//! Parentheses and `not` nest at most 64 levels deep.
+
+use crate::ProcessInfo;
+use regex::{Regex, RegexBuilder};
+use std::borrow::Cow;
+
+// Parentheses and 'not' recurse while parsing and evaluating, so deeper input is refused
+// rather than risking the stack
+const MAX_NESTING: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Number,
+    Text,
+}
+
+enum FieldValue<'a> {
+    Number(f64),
+    Text(Cow<'a, str>),
+}
+
+struct Field {
+    name: &'static str,
+    kind: Kind,
+    get: fn(&ProcessInfo) -> Option<FieldValue<'_>>,
+}
+
+fn number(value: impl Into<f64>) -> Option<FieldValue<'static>> {
+    Some(FieldValue::Number(value.into()))
+}
+
+fn text(value: &str) -> Option<FieldValue<'_>> {
+    Some(FieldValue::Text(Cow::Borrowed(value)))
+}
+
+// Enums serialize to their snake_case names, which is what users type
+fn enum_text<T: serde::Serialize>(value: Option<T>) -> Option<FieldValue<'static>> {
+    let value = serde_json::to_value(value?).ok()?;
+    Some(FieldValue::Text(Cow::Owned(value.as_str()?.to_string())))
+}
+
+const FIELDS: &[Field] = &[
+    Field { name: "pid", kind: Kind::Number, get: |p| number(p.pid) },
+    Field { name: "ppid", kind: Kind::Number, get: |p| number(p.ppid) },
+    Field { name: "name", kind: Kind::Text, get: |p| text(&p.name) },
+    Field { name: "cpu_usage", kind: Kind::Number, get: |p| number(p.cpu_usage) },
+    Field { name: "memory_usage", kind: Kind::Number, get: |p| number(p.memory_usage as f64) },
+    Field { name: "status", kind: Kind::Text, get: |p| text(&p.status) },
+    Field { name: "state", kind: Kind::Text, get: |p| enum_text(p.state) },
+    Field {
+        name: "state_letter",
+        kind: Kind::Text,
+        get: |p| Some(FieldValue::Text(Cow::Owned(p.state_letter?.to_string()))),
+    },
+    Field { name: "user", kind: Kind::Text, get: |p| text(&p.user) },
+    Field { name: "command", kind: Kind::Text, get: |p| text(&p.command) },
+    Field { name: "threads", kind: Kind::Number, get: |p| number(p.threads?) },
+    Field {
+        name: "environ",
+        kind: Kind::Text,
+        get: |p| Some(FieldValue::Text(Cow::Owned(p.environ.join("\n")))),
+    },
+    Field { name: "root", kind: Kind::Text, get: |p| text(&p.root) },
+    Field { name: "virtual_memory", kind: Kind::Number, get: |p| number(p.virtual_memory as f64) },
+    Field { name: "start_time", kind: Kind::Number, get: |p| number(p.start_time as f64) },
+    Field { name: "run_time", kind: Kind::Number, get: |p| number(p.run_time as f64) },
+    Field { name: "disk_read", kind: Kind::Number, get: |p| number(p.disk_usage.0 as f64) },
+    Field { name: "disk_written", kind: Kind::Number, get: |p| number(p.disk_usage.1 as f64) },
+    Field { name: "read_bytes_per_sec", kind: Kind::Number, get: |p| number(p.disk_io.read_bytes_per_sec) },
+    Field { name: "write_bytes_per_sec", kind: Kind::Number, get: |p| number(p.disk_io.write_bytes_per_sec) },
+    Field { name: "total_read_bytes", kind: Kind::Number, get: |p| number(p.disk_io.total_read_bytes as f64) },
+    Field {
+        name: "total_written_bytes",
+        kind: Kind::Number,
+        get: |p| number(p.disk_io.total_written_bytes as f64),
+    },
+    Field { name: "session_id", kind: Kind::Number, get: |p| number(p.session_id?) },
+    Field { name: "nice", kind: Kind::Number, get: |p| number(p.nice?) },
+    Field { name: "sched_policy", kind: Kind::Text, get: |p| enum_text(p.sched_policy) },
+    Field { name: "rt_priority", kind: Kind::Number, get: |p| number(p.rt_priority?) },
+    Field { name: "io_priority_class", kind: Kind::Text, get: |p| enum_text(p.io_priority_class) },
+    Field { name: "io_priority_level", kind: Kind::Number, get: |p| number(p.io_priority_level?) },
+    Field { name: "uid", kind: Kind::Number, get: |p| number(p.credentials.as_ref()?.real_uid) },
+    Field { name: "euid", kind: Kind::Number, get: |p| number(p.credentials.as_ref()?.effective_uid) },
+    Field { name: "gid", kind: Kind::Number, get: |p| number(p.credentials.as_ref()?.real_gid) },
+    Field { name: "egid", kind: Kind::Number, get: |p| number(p.credentials.as_ref()?.effective_gid) },
+    Field {
+        name: "uss",
+        kind: Kind::Number,
+        get: |p| number(p.memory_breakdown.as_ref()?.uss as f64),
+    },
+    Field {
+        name: "pss",
+        kind: Kind::Number,
+        get: |p| number(p.memory_breakdown.as_ref()?.pss as f64),
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Match,
+    NotMatch,
+}
+
+impl Op {
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Eq => "==",
+            Op::Ne => "!=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Match => "~",
+            Op::NotMatch => "!~",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Str(String),
+    Regex(String, bool),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    End,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(name) => format!("'{}'", name),
+            Token::Number(value) => format!("number {}", value),
+            Token::Str(value) => format!("string \"{}\"", value),
+            Token::Regex(pattern, _) => format!("regex /{}/", pattern),
+            Token::Op(op) => format!("'{}'", op.symbol()),
+            Token::And => "'and'".to_string(),
+            Token::Or => "'or'".to_string(),
+            Token::Not => "'not'".to_string(),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::End => "end of filter".to_string(),
+        }
+    }
+}
+
+fn error(column: usize, message: impl AsRef<str>) -> String {
+    format!("{} at column {}", message.as_ref(), column)
+}
+
+fn unit_multiplier(unit: &str) -> Option<f64> {
+    Some(match unit.to_ascii_lowercase().as_str() {
+        "" | "%" | "b" => 1.0,
+        "k" | "kb" | "kib" => 1024.0,
+        "m" | "mb" | "mib" => 1024.0 * 1024.0,
+        "g" | "gb" | "gib" => 1024.0 * 1024.0 * 1024.0,
+        "t" | "tb" | "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    })
+}
+
+/// Splits the input into tokens, each with its 1-based column.
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let next = chars.get(i + 1).copied();
+        let (token, width) = match (c, next) {
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            ('=', Some('=')) => (Token::Op(Op::Eq), 2),
+            ('!', Some('=')) => (Token::Op(Op::Ne), 2),
+            ('!', Some('~')) => (Token::Op(Op::NotMatch), 2),
+            ('!', _) => (Token::Not, 1),
+            ('>', Some('=')) => (Token::Op(Op::Ge), 2),
+            ('>', _) => (Token::Op(Op::Gt), 1),
+            ('<', Some('=')) => (Token::Op(Op::Le), 2),
+            ('<', _) => (Token::Op(Op::Lt), 1),
+            ('~', _) => (Token::Op(Op::Match), 1),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', _) => return Err(error(column, "Unexpected '=', use '==' to compare")),
+            ('"' | '\'', _) => {
+                let mut value = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(error(column, "Unterminated string")),
+                        Some('\\') if j + 1 < chars.len() => {
+                            value.push(chars[j + 1]);
+                            j += 2;
+                        }
+                        Some(&end) if end == c => break,
+                        Some(&other) => {
+                            value.push(other);
+                            j += 1;
+                        }
+                    }
+                }
+                tokens.push((Token::Str(value), column));
+                i = j + 1;
+                continue;
+            }
+            ('/', _) => {
+                let mut pattern = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(error(column, "Unterminated regex, expected a closing '/'")),
+                        // Only an escaped slash loses its backslash, the rest is regex syntax
+                        Some('\\') if chars.get(j + 1) == Some(&'/') => {
+                            pattern.push('/');
+                            j += 2;
+                        }
+                        Some('/') => break,
+                        Some(&other) => {
+                            pattern.push(other);
+                            j += 1;
+                        }
+                    }
+                }
+                j += 1;
+                let mut ignore_case = false;
+                while let Some(flag) = chars.get(j).filter(|flag| flag.is_ascii_alphabetic()) {
+                    match flag {
+                        'i' => ignore_case = true,
+                        other => {
+                            return Err(error(j + 1, format!("Unknown regex flag '{}', only 'i' is supported", other)))
+                        }
+                    }
+                    j += 1;
+                }
+                tokens.push((Token::Regex(pattern, ignore_case), column));
+                i = j;
+                continue;
+            }
+            (c, _) if c.is_ascii_digit() || (matches!(c, '-' | '.') && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let mut j = if c == '-' { i + 1 } else { i };
+                while chars.get(j).is_some_and(|d| d.is_ascii_digit() || *d == '.') {
+                    j += 1;
+                }
+                let digits: String = chars[i..j].iter().collect();
+                let unit_start = j;
+                while chars.get(j).is_some_and(|u| u.is_ascii_alphabetic() || *u == '%') {
+                    j += 1;
+                }
+                let unit: String = chars[unit_start..j].iter().collect();
+                let value: f64 = digits
+                    .parse()
+                    .map_err(|_| error(column, format!("Invalid number '{}'", digits)))?;
+                let multiplier = unit_multiplier(&unit).ok_or_else(|| {
+                    error(
+                        unit_start + 1,
+                        format!("Unknown unit '{}', expected B, KB, MB, GB, TB or %", unit),
+                    )
+                })?;
+                tokens.push((Token::Number(value * multiplier), column));
+                i = j;
+                continue;
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let mut j = i;
+                while chars.get(j).is_some_and(|w| w.is_alphanumeric() || *w == '_') {
+                    j += 1;
+                }
+                let word: String = chars[i..j].iter().collect();
+                let token = match word.to_ascii_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Ident(word),
+                };
+                tokens.push((token, column));
+                i = j;
+                continue;
+            }
+            (other, _) => return Err(error(column, format!("Unexpected character '{}'", other))),
+        };
+        tokens.push((token, column));
+        i += width;
+    }
+    tokens.push((Token::End, chars.len() + 1));
+    Ok(tokens)
+}
+
+enum Operand {
+    Number(f64),
+    Text(String),
+    Pattern(Regex),
+}
+
+enum Expr {
+    // Flat operand lists, so a long chain of terms does not nest
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Compare {
+        field: &'static Field,
+        op: Op,
+        operand: Operand,
+    },
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &(Token, usize) {
+        &self.tokens[self.position.min(self.tokens.len() - 1)]
+    }
+
+    fn next(&mut self) -> (Token, usize) {
+        let token = self.peek().clone();
+        self.position += 1;
+        token
+    }
+
+    fn enter(&mut self, column: usize) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return Err(error(
+                column,
+                format!("Filter is nested more than {} levels deep", MAX_NESTING),
+            ));
+        }
+        Ok(())
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut terms = vec![self.parse_and()?];
+        while self.peek().0 == Token::Or {
+            self.next();
+            terms.push(self.parse_and()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { Expr::Or(terms) })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut terms = vec![self.parse_not()?];
+        while self.peek().0 == Token::And {
+            self.next();
+            terms.push(self.parse_not()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { Expr::And(terms) })
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, String> {
+        if self.peek().0 == Token::Not {
+            let (_, column) = self.next();
+            self.enter(column)?;
+            let inner = self.parse_not()?;
+            self.depth -= 1;
+            return Ok(Expr::Not(Box::new(inner)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        let (token, column) = self.next();
+        match token {
+            Token::LParen => {
+                self.enter(column)?;
+                let inner = self.parse_or()?;
+                self.depth -= 1;
+                match self.next() {
+                    (Token::RParen, _) => Ok(inner),
+                    (other, column) => Err(error(column, format!("Expected ')' but found {}", other.describe()))),
+                }
+            }
+            Token::Ident(name) => self.parse_comparison(&name, column),
+            other => Err(error(column, format!("Expected a field name but found {}", other.describe()))),
+        }
+    }
+
+    fn parse_comparison(&mut self, name: &str, column: usize) -> Result<Expr, String> {
+        let field = FIELDS.iter().find(|field| field.name == name).ok_or_else(|| {
+            let names: Vec<&str> = FIELDS.iter().map(|field| field.name).collect();
+            format!(
+                "Unknown field '{}' at column {}, expected one of: {}",
+                name,
+                column,
+                names.join(", ")
+            )
+        })?;
+
+        let op = match self.next() {
+            (Token::Op(op), _) => op,
+            (other, column) => {
+                return Err(error(
+                    column,
+                    format!("Expected a comparison operator after '{}' but found {}", name, other.describe()),
+                ))
+            }
+        };
+
+        let (token, value_column) = self.next();
+        let operand = match (field.kind, op, token) {
+            (Kind::Number, Op::Match | Op::NotMatch, _) => {
+                return Err(error(
+                    value_column,
+                    format!("'{}' is numeric and cannot be matched with '{}'", name, op.symbol()),
+                ))
+            }
+            (Kind::Number, _, Token::Number(value)) => Operand::Number(value),
+            (Kind::Text, Op::Eq | Op::Ne, Token::Str(value)) => Operand::Text(value),
+            (Kind::Text, Op::Match | Op::NotMatch, Token::Str(pattern)) => Operand::Pattern(
+                Regex::new(&pattern).map_err(|e| format!("Invalid regex at column {}: {}", value_column, e))?,
+            ),
+            (Kind::Text, Op::Match | Op::NotMatch, Token::Regex(pattern, ignore_case)) => Operand::Pattern(
+                RegexBuilder::new(&pattern)
+                    .case_insensitive(ignore_case)
+                    .build()
+                    .map_err(|e| format!("Invalid regex at column {}: {}", value_column, e))?,
+            ),
+            (Kind::Text, Op::Gt | Op::Ge | Op::Lt | Op::Le, _) => {
+                return Err(error(
+                    value_column,
+                    format!("'{}' is text and cannot be compared with '{}'", name, op.symbol()),
+                ))
+            }
+            (Kind::Number, _, other) => {
+                return Err(error(
+                    value_column,
+                    format!("'{}' is numeric, expected a number but found {}", name, other.describe()),
+                ))
+            }
+            (Kind::Text, Op::Eq | Op::Ne, other) => {
+                return Err(error(
+                    value_column,
+                    format!("'{}' is text, expected a quoted string but found {}", name, other.describe()),
+                ))
+            }
+            (Kind::Text, _, other) => {
+                return Err(error(
+                    value_column,
+                    format!("Expected a /regex/ or a quoted string but found {}", other.describe()),
+                ))
+            }
+        };
+        Ok(Expr::Compare { field, op, operand })
+    }
+}
+
+fn compare(value: FieldValue, op: Op, operand: &Operand) -> bool {
+    match (value, operand) {
+        (FieldValue::Number(value), Operand::Number(target)) => match op {
+            Op::Eq => value == *target,
+            Op::Ne => value != *target,
+            Op::Gt => value > *target,
+            Op::Ge => value >= *target,
+            Op::Lt => value < *target,
+            Op::Le => value <= *target,
+            Op::Match | Op::NotMatch => false,
+        },
+        (FieldValue::Text(value), Operand::Text(target)) => match op {
+            Op::Eq => value == target.as_str(),
+            Op::Ne => value != target.as_str(),
+            _ => false,
+        },
+        (FieldValue::Text(value), Operand::Pattern(pattern)) => match op {
+            Op::Match => pattern.is_match(&value),
+            Op::NotMatch => !pattern.is_match(&value),
+            _ => false,
+        },
+        // Parsing rejects every other combination
+        _ => false,
+    }
+}
+
+impl Expr {
+    fn matches(&self, process: &ProcessInfo) -> bool {
+        match self {
+            Expr::And(terms) => terms.iter().all(|term| term.matches(process)),
+            Expr::Or(terms) => terms.iter().any(|term| term.matches(process)),
+            Expr::Not(inner) => !inner.matches(process),
+            Expr::Compare { field, op, operand } => {
+                (field.get)(process).is_some_and(|value| compare(value, *op, operand))
+            }
+        }
+    }
+}
+
+/// A parsed filter expression, ready to be evaluated against many processes.
+pub struct Filter {
+    root: Expr,
+}
+
+impl Filter {
+    /// Parses `input`; errors describe what was expected and at which column.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            position: 0,
+            depth: 0,
+        };
+        if parser.peek().0 == Token::End {
+            return Err("Filter is empty".to_string());
+        }
+        let root = parser.parse_or()?;
+        match parser.next() {
+            (Token::End, _) => Ok(Self { root }),
+            (other, column) => Err(error(
+                column,
+                format!("Expected 'and', 'or' or the end of the filter but found {}", other.describe()),
+            )),
+        }
+    }
+
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        self.root.matches(process)
+    }
+}
+
+/// Parses a filter without running it, so the UI can report errors while the user types.
+#[tauri::command]
+pub async fn check_process_filter(filter: String) -> Result<(), String> {
+    Filter::parse(&filter).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, name: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            command: format!("/usr/bin/{} --flag", name),
+            user: "postgres".to_string(),
+            ..ProcessInfo::default()
+        }
+    }
+
+    fn matches(filter: &str, process: &ProcessInfo) -> bool {
+        Filter::parse(filter).unwrap().matches(process)
+    }
+
+    fn parse_error(filter: &str) -> String {
+        Filter::parse(filter).err().expect("filter should not parse")
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let filter = "pid == 1 or pid == 2 and pid == 3";
+        assert!(matches(filter, &process(1, "a")));
+        assert!(!matches(filter, &process(2, "a")));
+        assert!(matches("(pid == 1 or pid == 2) and name == 'a'", &process(2, "a")));
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and() {
+        let filter = "not pid == 1 and name == 'a'";
+        assert!(matches(filter, &process(2, "a")));
+        assert!(!matches(filter, &process(1, "a")));
+        assert!(matches("!!(pid == 1)", &process(1, "a")));
+    }
+
+    #[test]
+    fn numbers_take_units() {
+        let mut big = process(1, "a");
+        big.memory_usage = 1537;
+        big.cpu_usage = 50.5;
+        assert!(matches("memory_usage > 1.5KB", &big));
+        assert!(!matches("memory_usage > 2kb", &big));
+        assert!(matches("memory_usage < 1MiB and cpu_usage >= 50%", &big));
+        assert!(parse_error("memory_usage > 3parsecs").contains("Unknown unit 'parsecs'"));
+    }
+
+    #[test]
+    fn missing_values_match_nothing() {
+        let p = process(1, "a");
+        assert!(!matches("nice == 0", &p));
+        assert!(!matches("nice != 0", &p));
+        assert!(matches("not nice == 0", &p));
+    }
+
+    #[test]
+    fn regexes() {
+        let p = process(1, "Firefox");
+        assert!(matches("name ~ /^fire/i", &p));
+        assert!(!matches("name ~ /^fire/", &p));
+        assert!(matches("name !~ /chrome/", &p));
+        assert!(matches(r"command ~ /usr\/bin\/Fire/", &p));
+        assert!(matches("command ~ '--flag$'", &p));
+        assert!(parse_error("name ~ /x/g").contains("Unknown regex flag 'g'"));
+        assert!(parse_error("name ~ /(/").starts_with("Invalid regex at column 8"));
+        assert!(parse_error("name ~ /abc").contains("Unterminated regex"));
+    }
+
+    #[test]
+    fn strings_take_either_quote_and_escapes() {
+        let p = process(1, r#"say "hi""#);
+        assert!(matches(r#"name == 'say "hi"'"#, &p));
+        assert!(matches(r#"name == "say \"hi\"""#, &p));
+        assert!(matches("user == 'postgres' && name != 'x'", &p));
+        assert_eq!(parse_error("name == 'abc"), "Unterminated string at column 9");
+    }
+
+    #[test]
+    fn unknown_fields_list_the_known_ones() {
+        let error = parse_error("cpu_usage > 1 and bogus == 1");
+        let expected = "Unknown field 'bogus' at column 19, expected one of: pid, ppid,";
+        assert!(error.starts_with(expected), "{}", error);
+    }
+
+    #[test]
+    fn syntax_errors_name_the_column() {
+        assert_eq!(parse_error(""), "Filter is empty");
+        assert_eq!(parse_error("pid = 1"), "Unexpected '=', use '==' to compare at column 5");
+        assert_eq!(
+            parse_error("pid =="),
+            "'pid' is numeric, expected a number but found end of filter at column 7"
+        );
+        assert_eq!(parse_error("(pid == 1"), "Expected ')' but found end of filter at column 10");
+        assert_eq!(
+            parse_error("pid == 1 pid == 2"),
+            "Expected 'and', 'or' or the end of the filter but found 'pid' at column 10"
+        );
+        assert_eq!(
+            parse_error("name > 'a'"),
+            "'name' is text and cannot be compared with '>' at column 8"
+        );
+        assert_eq!(
+            parse_error("pid ~ /1/"),
+            "'pid' is numeric and cannot be matched with '~' at column 7"
+        );
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let nested = |depth: usize| format!("{}pid == 1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Filter::parse(&nested(MAX_NESTING)).is_ok());
+        let too_deep = "Filter is nested more than 64 levels deep";
+        assert!(parse_error(&nested(MAX_NESTING + 1)).starts_with(too_deep));
+        assert!(parse_error(&"not ".repeat(100_000)).starts_with(too_deep));
+    }
+
+    #[test]
+    fn long_chains_stay_flat() {
+        let terms = |op: &str| {
+            let terms: Vec<String> = (1..=100_000).map(|pid| format!("pid == {}", pid)).collect();
+            terms.join(op)
+        };
+        let any = Filter::parse(&terms(" or ")).unwrap();
+        assert!(any.matches(&process(100_000, "a")));
+        assert!(!any.matches(&process(100_001, "a")));
+        let all = Filter::parse(&terms(" and ")).unwrap();
+        assert!(!all.matches(&process(1, "a")));
+        drop((any, all));
+    }
+}
@@ -0,0 +1,379 @@
+use crate::{AppState, ProcessInfo, SystemStats};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use tauri::State;
+
+const DEFAULT_RETENTION_MINUTES: u64 = 10;
+const MAX_RETENTION_MINUTES: u64 = 24 * 60;
+// Every process gets its own series, so these stay fixed and well below the system history
+const PROCESS_RETENTION_MS: u64 = 5 * 60_000;
+const MAX_PROCESS_SAMPLES: usize = 300;
+
+// What the retention window holds at the fastest sampler interval. Time-based pruning
+// normally applies first; this only bounds callers that sample faster, like --batch.
+fn max_samples(retention_ms: u64) -> usize {
+    (retention_ms / crate::sampler::MIN_INTERVAL_MS).max(1) as usize
+}
+
+#[derive(Serialize, Clone)]
+pub struct SystemSample {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub stats: SystemStats,
+}
+
+pub struct SystemHistory {
+    samples: VecDeque<SystemSample>,
+    retention_ms: u64,
+}
+
+impl Default for SystemHistory {
+    fn default() -> Self {
+        Self {
+            samples: VecDeque::new(),
+            retention_ms: DEFAULT_RETENTION_MINUTES * 60_000,
+        }
+    }
+}
+
+impl SystemHistory {
+    pub fn record(&mut self, timestamp: u64, stats: &SystemStats) {
+        self.samples.push_back(SystemSample {
+            timestamp,
+            stats: stats.clone(),
+        });
+        self.prune(timestamp);
+    }
+
+    pub fn set_retention_minutes(&mut self, minutes: u64) {
+        self.retention_ms = minutes * 60_000;
+        if let Some(newest) = self.samples.back().map(|sample| sample.timestamp) {
+            self.prune(newest);
+        }
+    }
+
+    fn prune(&mut self, now: u64) {
+        let cutoff = now.saturating_sub(self.retention_ms);
+        while self
+            .samples
+            .front()
+            .is_some_and(|sample| sample.timestamp < cutoff)
+            || self.samples.len() > max_samples(self.retention_ms)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Returns the samples from the last `range_ms`, averaged into buckets of
+    /// `resolution_ms` when a resolution is given.
+    pub fn query(&self, now: u64, range_ms: Option<u64>, resolution_ms: Option<u64>) -> Vec<SystemSample> {
+        let since = range_ms.map_or(0, |range| now.saturating_sub(range));
+        let samples = self.samples.iter().filter(|sample| sample.timestamp >= since);
+
+        let resolution_ms = match resolution_ms {
+            Some(resolution) if resolution > 0 => resolution,
+            _ => return samples.cloned().collect(),
+        };
+
+        let mut buckets: Vec<SystemSample> = Vec::new();
+        let mut bucket: Vec<&SystemSample> = Vec::new();
+        for sample in samples {
+            let same_bucket = bucket
+                .first()
+                .is_some_and(|first| first.timestamp / resolution_ms == sample.timestamp / resolution_ms);
+            if !same_bucket && !bucket.is_empty() {
+                buckets.push(average(&bucket));
+                bucket.clear();
+            }
+            bucket.push(sample);
+        }
+        if !bucket.is_empty() {
+            buckets.push(average(&bucket));
+        }
+        buckets
+    }
+}
+
+fn mean_u64(values: impl Iterator<Item = u64>, count: usize) -> u64 {
+    (values.map(u128::from).sum::<u128>() / count as u128) as u64
+}
+
+// Rates and usage are averaged, capacities and counters take the latest value
+fn average(bucket: &[&SystemSample]) -> SystemSample {
+    let count = bucket.len();
+    let last = bucket[count - 1];
+
+    let cores = last.stats.cpu_usage.len();
+    let cpu_usage = (0..cores)
+        .map(|core| {
+            bucket
+                .iter()
+                .map(|sample| sample.stats.cpu_usage.get(core).copied().unwrap_or(0.0))
+                .sum::<f32>()
+                / count as f32
+        })
+        .collect();
+
+    let mut load_avg = [0.0; 3];
+    for (i, value) in load_avg.iter_mut().enumerate() {
+        *value = bucket.iter().map(|sample| sample.stats.load_avg[i]).sum::<f64>() / count as f64;
+    }
+
+    SystemSample {
+        timestamp: last.timestamp,
+        stats: SystemStats {
+            cpu_usage,
+            memory_used: mean_u64(bucket.iter().map(|sample| sample.stats.memory_used), count),
+            memory_free: mean_u64(bucket.iter().map(|sample| sample.stats.memory_free), count),
+            memory_cached: mean_u64(bucket.iter().map(|sample| sample.stats.memory_cached), count),
+            load_avg,
+            network_rx_bytes: mean_u64(bucket.iter().map(|sample| sample.stats.network_rx_bytes), count),
+            network_tx_bytes: mean_u64(bucket.iter().map(|sample| sample.stats.network_tx_bytes), count),
+            ..last.stats.clone()
+        },
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct ProcessSample {
+    pub timestamp: u64,
+    pub cpu_usage: f32,
+    pub memory_usage: u64,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    pub threads: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct ProcessSeries {
+    pub pid: u32,
+    pub start_time: u64,
+    pub samples: Vec<ProcessSample>,
+}
+
+/// Per-process samples keyed by (pid, start_time), so a reused PID starts a fresh series.
+/// Keeps the last PROCESS_RETENTION_MS, and at most MAX_PROCESS_SAMPLES per series,
+/// whatever the system history retention is.
+#[derive(Default)]
+pub struct ProcessHistory {
+    series: HashMap<(u32, u64), VecDeque<ProcessSample>>,
+}
+
+impl ProcessHistory {
+    pub fn record(&mut self, timestamp: u64, processes: &[ProcessInfo]) {
+        for process in processes {
+            let series = self.series.entry((process.pid, process.start_time)).or_default();
+            series.push_back(ProcessSample {
+                timestamp,
+                cpu_usage: process.cpu_usage,
+                memory_usage: process.memory_usage,
+                read_bytes_per_sec: process.disk_io.read_bytes_per_sec,
+                write_bytes_per_sec: process.disk_io.write_bytes_per_sec,
+                threads: process.threads,
+            });
+            while series.len() > MAX_PROCESS_SAMPLES {
+                series.pop_front();
+            }
+        }
+        self.prune(timestamp);
+    }
+
+    // Exited processes keep their series until the last sample ages out
+    fn prune(&mut self, now: u64) {
+        let cutoff = now.saturating_sub(PROCESS_RETENTION_MS);
+        self.series.retain(|_, samples| {
+            while samples.front().is_some_and(|sample| sample.timestamp < cutoff) {
+                samples.pop_front();
+            }
+            !samples.is_empty()
+        });
+    }
+
+    /// Returns the samples of the newest process that had each PID.
+    pub fn query(&self, pids: &[u32], now: u64, window_ms: Option<u64>) -> Vec<ProcessSeries> {
+        let since = window_ms.map_or(0, |window| now.saturating_sub(window));
+        pids.iter()
+            .filter_map(|pid| {
+                let (key, samples) = self
+                    .series
+                    .iter()
+                    .filter(|((series_pid, _), _)| series_pid == pid)
+                    .max_by_key(|((_, start_time), _)| *start_time)?;
+                Some(ProcessSeries {
+                    pid: key.0,
+                    start_time: key.1,
+                    samples: samples
+                        .iter()
+                        .filter(|sample| sample.timestamp >= since)
+                        .cloned()
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Returns the samples of the last `range_ms`, averaged into `resolution_ms` buckets.
+#[tauri::command]
+pub async fn get_history(
+    range_ms: Option<u64>,
+    resolution_ms: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SystemSample>, String> {
+    let history = state.history.lock().map_err(|_| "Failed to lock history")?;
+    Ok(history.query(crate::unix_millis()?, range_ms, resolution_ms))
+}
+
+/// Sets how long system history is kept. Process history keeps a fixed, shorter window.
+#[tauri::command]
+pub async fn set_history_retention(minutes: u64, state: State<'_, AppState>) -> Result<(), String> {
+    if minutes == 0 || minutes > MAX_RETENTION_MINUTES {
+        return Err(format!(
+            "History retention must be between 1 and {} minutes",
+            MAX_RETENTION_MINUTES
+        ));
+    }
+    state
+        .history
+        .lock()
+        .map_err(|_| "Failed to lock history")?
+        .set_retention_minutes(minutes);
+    Ok(())
+}
+
+/// Returns the last `window_ms` of samples for each PID, or everything retained.
+#[tauri::command]
+pub async fn get_process_history(
+    pids: Vec<u32>,
+    window_ms: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ProcessSeries>, String> {
+    let history = state.process_history.lock().map_err(|_| "Failed to lock process history")?;
+    Ok(history.query(&pids, crate::unix_millis()?, window_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Averaged fields and latest-value fields all carry `value`, so buckets show which is which
+    fn stats(value: u64) -> SystemStats {
+        SystemStats {
+            cpu_usage: vec![value as f32],
+            memory_total: value,
+            memory_used: value,
+            memory_free: value,
+            memory_cached: value,
+            uptime: value,
+            load_avg: [value as f64; 3],
+            network_rx_bytes: value,
+            network_tx_bytes: value,
+            disk_total_bytes: value,
+            disk_used_bytes: value,
+            disk_free_bytes: value,
+            process_states: Default::default(),
+        }
+    }
+
+    fn system_history(samples: &[(u64, u64)]) -> SystemHistory {
+        let mut history = SystemHistory::default();
+        for (timestamp, value) in samples {
+            history.record(*timestamp, &stats(*value));
+        }
+        history
+    }
+
+    fn timestamps(samples: &[SystemSample]) -> Vec<u64> {
+        samples.iter().map(|sample| sample.timestamp).collect()
+    }
+
+    #[test]
+    fn averages_rates_and_keeps_the_latest_capacities() {
+        let history = system_history(&[(0, 1), (500, 3), (999, 5), (1_000, 10), (2_500, 20)]);
+        let buckets = history.query(2_500, None, Some(1_000));
+        // Buckets split on multiples of the resolution and take their last timestamp
+        assert_eq!(timestamps(&buckets), [999, 1_000, 2_500]);
+
+        let first = &buckets[0].stats;
+        assert_eq!(first.cpu_usage, [3.0]);
+        assert_eq!(first.load_avg, [3.0; 3]);
+        assert_eq!(
+            (first.memory_used, first.memory_free, first.memory_cached),
+            (3, 3, 3)
+        );
+        assert_eq!((first.network_rx_bytes, first.network_tx_bytes), (3, 3));
+        assert_eq!((first.memory_total, first.uptime, first.disk_used_bytes), (5, 5, 5));
+        assert_eq!(buckets[1].stats.memory_used, 10);
+    }
+
+    #[test]
+    fn returns_raw_samples_without_a_resolution() {
+        let history = system_history(&[(0, 1), (500, 3), (999, 5)]);
+        for resolution_ms in [None, Some(0)] {
+            assert_eq!(timestamps(&history.query(999, None, resolution_ms)), [0, 500, 999]);
+        }
+    }
+
+    #[test]
+    fn range_cuts_off_older_samples() {
+        let history = system_history(&[(0, 1), (1_000, 2), (2_500, 3)]);
+        assert_eq!(timestamps(&history.query(2_500, Some(1_500), None)), [1_000, 2_500]);
+        assert_eq!(timestamps(&history.query(2_500, Some(0), None)), [2_500]);
+        assert_eq!(timestamps(&history.query(2_500, Some(10_000), None)), [0, 1_000, 2_500]);
+    }
+
+    #[test]
+    fn shrinking_retention_prunes_right_away() {
+        let mut history = system_history(&[(0, 1), (60_000, 2), (120_000, 3)]);
+        assert_eq!(history.samples.len(), 3);
+        history.set_retention_minutes(1);
+        assert_eq!(timestamps(&history.query(120_000, None, None)), [60_000, 120_000]);
+    }
+
+    fn process(pid: u32, start_time: u64, cpu_usage: f32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            start_time,
+            cpu_usage,
+            ..ProcessInfo::default()
+        }
+    }
+
+    fn cpu<'a>(samples: impl IntoIterator<Item = &'a ProcessSample>) -> Vec<f32> {
+        samples.into_iter().map(|sample| sample.cpu_usage).collect()
+    }
+
+    #[test]
+    fn reused_pid_starts_a_new_series() {
+        let mut history = ProcessHistory::default();
+        history.record(1_000, &[process(7, 100, 1.0)]);
+        history.record(2_000, &[process(7, 100, 2.0)]);
+        history.record(3_000, &[process(7, 200, 3.0)]);
+        assert_eq!(history.series.len(), 2);
+        assert_eq!(cpu(&history.series[&(7, 100)]), [1.0, 2.0]);
+
+        // Queries pick the newest process to have the PID
+        let found = history.query(&[7, 8], 3_000, None);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].start_time, 200);
+        assert_eq!(cpu(&found[0].samples), [3.0]);
+        assert_eq!(cpu(&history.query(&[7], 3_000, Some(0))[0].samples), [3.0]);
+    }
+
+    #[test]
+    fn series_are_bounded_and_exited_ones_age_out() {
+        let mut history = ProcessHistory::default();
+        for second in 0..MAX_PROCESS_SAMPLES as u64 + 10 {
+            history.record(second * 100, &[process(1, 1, second as f32)]);
+        }
+        assert_eq!(history.series[&(1, 1)].len(), MAX_PROCESS_SAMPLES);
+
+        history.record(PROCESS_RETENTION_MS, &[process(2, 1, 0.0)]);
+        assert_eq!(history.series.len(), 2);
+        // PID 1 exited; its series goes once its last sample is older than the retention
+        history.record(PROCESS_RETENTION_MS * 2, &[process(2, 1, 0.0)]);
+        assert!(history.query(&[1], PROCESS_RETENTION_MS * 2, None).is_empty());
+        assert_eq!(history.series.len(), 1);
+    }
+}
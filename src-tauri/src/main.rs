@@ -1,8 +1,29 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod alerts;
+mod batch;
+mod connections;
+mod delta;
+mod disk_io;
+mod export;
+mod fds;
+mod filter;
+mod history;
+mod memory_maps;
+mod metrics;
+mod process_control;
+mod process_state;
+mod process_tree;
+mod procfs;
+mod query;
+mod recording;
+mod sampler;
+mod scheduling;
+mod threads;
+mod users;
+
 use sysinfo::{
     System,
-    ProcessStatus,
     NetworksExt,
     NetworkExt,
     Disk,
@@ -12,15 +33,32 @@ use sysinfo::{
     ProcessExt,
     PidExt,
 };
-use tauri::State;
-use std::sync::Mutex;
-use std::collections::HashMap;
+use tauri::{Manager, State};
+use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use process_state::ProcessState;
 
 struct AppState {
     sys: Mutex<System>,
-    process_cache: Mutex<HashMap<u32, ProcessStaticInfo>>,
+    // Keyed by (pid, start_time) so a reused PID never inherits a dead process's details.
+    // start_time has whole second granularity, so a PID reused within the same second
+    // still looks like the old process; the exec check below catches it when the
+    // program name differs.
+    process_cache: Mutex<HashMap<(u32, u64), ProcessStaticInfo>>,
     last_network_update: Mutex<(Instant, u64, u64)>,
+    thread_samples: Mutex<HashMap<u32, threads::ThreadSample>>,
+    io_rates: Mutex<disk_io::IoRateTracker>,
+    memory_breakdowns: Mutex<memory_maps::BreakdownCache>,
+    history: Mutex<history::SystemHistory>,
+    process_history: Mutex<history::ProcessHistory>,
+    recording: Mutex<recording::RecordingState>,
+    playback: Mutex<Option<recording::Player>>,
+    metrics: Mutex<Option<metrics::MetricsServer>>,
+    alerts: Mutex<alerts::AlertEngine>,
+    users: Mutex<users::UserCache>,
+    sampler: sampler::Sampler,
+    views: Mutex<delta::ProcessViews>,
 }
 
 impl AppState {
@@ -36,6 +74,18 @@ impl AppState {
             sys: Mutex::new(sys),
             process_cache: Mutex::new(HashMap::new()),
             last_network_update: Mutex::new((Instant::now(), initial_rx, initial_tx)),
+            thread_samples: Mutex::new(HashMap::new()),
+            io_rates: Mutex::new(disk_io::IoRateTracker::default()),
+            memory_breakdowns: Mutex::new(memory_maps::BreakdownCache::default()),
+            history: Mutex::new(history::SystemHistory::default()),
+            process_history: Mutex::new(history::ProcessHistory::default()),
+            recording: Mutex::new(recording::RecordingState::default()),
+            playback: Mutex::new(None),
+            metrics: Mutex::new(None),
+            alerts: Mutex::new(alerts::AlertEngine::default()),
+            users: Mutex::new(users::UserCache::default()),
+            sampler: sampler::Sampler::default(),
+            views: Mutex::new(delta::ProcessViews::default()),
         }
     }
 }
@@ -44,17 +94,22 @@ impl AppState {
 struct ProcessStaticInfo {
     name: String,
     command: String,
-    user: String,
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
 struct ProcessInfo {
     pid: u32,
     ppid: u32,
     name: String,
     cpu_usage: f32,
     memory_usage: u64,
+    /// Display label of `state`, kept for the status filter and older clients
     status: String,
+    state: Option<ProcessState>,
+    /// Raw state letter from the kernel, Linux only
+    state_letter: Option<char>,
+    /// Name of the real user, or the raw ID when it cannot be resolved. The effective
+    /// user, which differs for setuid programs, is in `credentials`.
     user: String,
     command: String,
     threads: Option<u32>,
@@ -63,11 +118,21 @@ struct ProcessInfo {
     virtual_memory: u64,
     start_time: u64,
     run_time: u64,
-    disk_usage: (u64, u64),  // (read_bytes, written_bytes)
+    disk_usage: (u64, u64),  // (read_bytes, written_bytes) since the previous refresh
+    disk_io: disk_io::DiskIo,
     session_id: Option<u32>,
+    nice: Option<i32>,
+    sched_policy: Option<scheduling::SchedPolicy>,
+    rt_priority: Option<i32>,
+    io_priority_class: Option<scheduling::IoClass>,
+    io_priority_level: Option<u8>,
+    cpu_affinity: Option<Vec<usize>>,
+    /// Lags behind the other fields by up to a few samples, see memory_maps::BreakdownCache
+    memory_breakdown: Option<memory_maps::MemoryBreakdown>,
+    credentials: Option<users::Credentials>,
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct SystemStats {
     pub cpu_usage: Vec<f32>,
     pub memory_total: u64,
@@ -81,6 +146,9 @@ pub struct SystemStats {
     pub disk_total_bytes: u64,
     pub disk_used_bytes: u64,
     pub disk_free_bytes: u64,
+    /// Number of processes in each state
+    #[serde(default)]
+    pub process_states: BTreeMap<ProcessState, usize>,
 }
 
 // Assume MacOS or Linux
@@ -98,10 +166,45 @@ fn filter_disks(disks: &[Disk]) -> Vec<&sysinfo::Disk> {
     disks.iter().collect()
 }
 
-#[tauri::command]
-async fn get_processes(state: State<'_, AppState>) -> Result<(Vec<ProcessInfo>, SystemStats), String> {
+fn unix_millis() -> Result<u64, String> {
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_millis() as u64)
+}
+
+/// Takes a snapshot with read_processes and feeds it to history and alerts.
+fn collect_processes(state: &AppState) -> Result<(Vec<ProcessInfo>, SystemStats), String> {
+    let (processes, system_stats) = read_processes(state)?;
+
+    let timestamp = unix_millis()?;
+    state
+        .history
+        .lock()
+        .map_err(|_| "Failed to lock history")?
+        .record(timestamp, &system_stats);
+
+    state
+        .process_history
+        .lock()
+        .map_err(|_| "Failed to lock process history")?
+        .record(timestamp, &processes);
+
+    state
+        .alerts
+        .lock()
+        .map_err(|_| "Failed to lock alerts")?
+        .evaluate(timestamp, &processes, &system_stats);
+
+    Ok((processes, system_stats))
+}
+
+/// Refreshes the system and takes one snapshot of every process plus the system stats.
+/// Batch mode uses this directly, as it keeps no history and raises no alerts.
+fn read_processes(state: &AppState) -> Result<(Vec<ProcessInfo>, SystemStats), String> {
     let processes_data;
-    let system_stats;
+    let mut system_stats;
+    let sampled_at;
     
     // Get current time once for all calculations
     let current_time = SystemTime::now()
@@ -116,6 +219,7 @@ async fn get_processes(state: State<'_, AppState>) -> Result<(Vec<ProcessInfo>,
         sys.refresh_networks();
         sys.refresh_disks_list();
         sys.refresh_disks();
+        sampled_at = Instant::now();
 
         // Collect all the process data we need while holding sys lock
         processes_data = sys
@@ -134,9 +238,10 @@ async fn get_processes(state: State<'_, AppState>) -> Result<(Vec<ProcessInfo>,
                     process.name().to_string(),
                     process.cmd().to_vec(),
                     process.user_id().map(|uid| uid.to_string()),
+                    users::sysinfo_ids(process),
                     process.cpu_usage(),
                     process.memory(),
-                    process.status(),
+                    ProcessState::from(process.status()),
                     process.parent().map(|p| p.as_u32()),
                     process.environ().to_vec(),
                     process.root().to_string_lossy().into_owned(),
@@ -145,6 +250,8 @@ async fn get_processes(state: State<'_, AppState>) -> Result<(Vec<ProcessInfo>,
                     run_time,  // Use calculated run_time
                     process.disk_usage().read_bytes,
                     process.disk_usage().written_bytes,
+                    process.disk_usage().total_read_bytes,
+                    process.disk_usage().total_written_bytes,
                     process.session_id().map(|id| id.as_u32()),
                 )
             })
@@ -166,7 +273,7 @@ async fn get_processes(state: State<'_, AppState>) -> Result<(Vec<ProcessInfo>,
         *last_update = (current_time, current_rx, current_tx);
 
         // Calculate total disk usage
-        let disk_stats = filter_disks(&sys.disks())
+        let disk_stats = filter_disks(sys.disks())
             .iter()
             .fold((0, 0, 0), |acc, disk| {
                 (
@@ -189,32 +296,65 @@ async fn get_processes(state: State<'_, AppState>) -> Result<(Vec<ProcessInfo>,
             disk_total_bytes: disk_stats.0,
             disk_used_bytes: disk_stats.1,
             disk_free_bytes: disk_stats.2,
+            // Counted from the rows below, so the totals match their states
+            process_states: BTreeMap::new(),
         };
     } // sys lock is automatically dropped here
 
     // Now lock the process cache
     let mut process_cache = state.process_cache.lock().map_err(|_| "Failed to lock process cache")?;
 
+    let mut io_rates = state.io_rates.lock().map_err(|_| "Failed to lock I/O rates")?;
+
+    let mut user_cache = state.users.lock().map_err(|_| "Failed to lock user cache")?;
+
+    let mut memory_breakdowns = state
+        .memory_breakdowns
+        .lock()
+        .map_err(|_| "Failed to lock memory breakdowns")?;
+    memory_breakdowns.begin_sample();
+
     // Build the process info list
-    let processes = processes_data
+    let processes: Vec<ProcessInfo> = processes_data
         .into_iter()
-        .map(|(pid, name, cmd, user_id, cpu_usage, memory, status, ppid, 
+        .map(|(pid, name, cmd, user_id, ids, cpu_usage, memory, status, ppid, 
                environ, root, virtual_memory, start_time, run_time, 
-               disk_read, disk_written, session_id)| {
-            let static_info = process_cache.entry(pid).or_insert_with(|| {
-                ProcessStaticInfo {
+               disk_read, disk_written, total_read, total_written, session_id)| {
+            let stat = procfs::read_stat(pid);
+            let static_info = process_cache
+                .entry((pid, start_time))
+                .and_modify(|info| {
+                    // exec keeps the PID and start time but swaps the program. sysinfo reads the
+                    // name and argv only when it first sees a PID, so compare the comm of the stat
+                    // we already read and fetch the new argv only when it changed. This misses an
+                    // exec of a program with the same name, and every exec where there is no /proc.
+                    // Zombies have no argv left and keep the one we had.
+                    if let Some(stat) = stat.as_ref().filter(|stat| stat.comm != info.name) {
+                        if let Some(cmd) = procfs::read_cmdline(pid).filter(|cmd| !cmd.is_empty()) {
+                            info.command = cmd.join(" ");
+                        }
+                        info.name = stat.comm.clone();
+                    }
+                })
+                .or_insert_with(|| ProcessStaticInfo {
                     name: name.clone(),
                     command: cmd.join(" "),
-                    user: user_id.unwrap_or_else(|| "-".to_string()),
-                }
-            });
+                });
 
-            let status_str = match status {
-                ProcessStatus::Run => "Running",
-                ProcessStatus::Sleep => "Sleeping",
-                ProcessStatus::Idle => "Idle",
-                _ => "Unknown"
+            let credentials = procfs::read_status(pid)
+                .as_ref()
+                .map(users::proc_ids)
+                .or(ids)
+                .map(|ids| user_cache.credentials(ids));
+            let user = match &credentials {
+                Some(credentials) => credentials.real_user.clone(),
+                None => user_id.unwrap_or_else(|| "-".to_string()),
             };
+            // sysinfo parsed its own read of stat, take the state from ours so it matches the letter
+            #[cfg(target_os = "linux")]
+            let status = stat.as_ref().map_or(status, |stat| ProcessState::from_letter(stat.state));
+            let scheduling = scheduling::read_scheduling(pid);
+            let io_rate = io_rates.update(pid, start_time, sampled_at, total_read, total_written);
 
             ProcessInfo {
                 pid,
@@ -222,24 +362,66 @@ async fn get_processes(state: State<'_, AppState>) -> Result<(Vec<ProcessInfo>,
                 name: static_info.name.clone(),
                 cpu_usage,
                 memory_usage: memory,
-                status: status_str.to_string(),
-                user: static_info.user.clone(),
+                status: status.label().to_string(),
+                state: Some(status),
+                state_letter: stat.as_ref().map(|stat| stat.state),
+                user,
                 command: static_info.command.clone(),
-                threads: None,
+                threads: threads::thread_count(pid, stat.as_ref()),
                 environ,
                 root,
                 virtual_memory,
                 start_time,
                 run_time,
                 disk_usage: (disk_read, disk_written),
+                disk_io: disk_io::build_disk_io(io_rate, total_read, total_written, disk_io::read_proc_io(pid)),
                 session_id,
+                nice: scheduling.nice,
+                sched_policy: scheduling.sched_policy,
+                rt_priority: scheduling.rt_priority,
+                io_priority_class: scheduling.io_priority_class,
+                io_priority_level: scheduling.io_priority_level,
+                cpu_affinity: scheduling.cpu_affinity,
+                memory_breakdown: memory_breakdowns.get(pid, start_time, sampled_at),
+                credentials,
             }
         })
         .collect();
 
+    io_rates.retain_pids(&processes.iter().map(|p| p.pid).collect::<HashSet<_>>());
+    // Forget processes that have exited since the last sample
+    let live: HashSet<(u32, u64)> = processes.iter().map(|p| (p.pid, p.start_time)).collect();
+    process_cache.retain(|key, _| live.contains(key));
+    memory_breakdowns.retain(&live);
+
+    for state in processes.iter().filter_map(|process| process.state) {
+        *system_stats.process_states.entry(state).or_insert(0) += 1;
+    }
+
     Ok((processes, system_stats))
 }
 
+/// Returns what the UI is currently looking at: the playback frame if a
+/// recording is open, the sampler's latest snapshot otherwise.
+/// The snapshot is shared, so callers clone only the rows they hand out.
+fn current_snapshot(state: &AppState) -> Result<Arc<sampler::Snapshot>, String> {
+    // While a recording is being played back it stands in for live data
+    if let Some(frame) = recording::playback_frame(state)? {
+        return Ok(frame);
+    }
+    state.sampler.latest()
+}
+
+/// Returns one page of the current snapshot; without a query that is every process.
+#[tauri::command]
+async fn get_processes(
+    query: Option<query::ProcessQuery>,
+    state: State<'_, AppState>,
+) -> Result<query::ProcessPage, String> {
+    let snapshot = current_snapshot(&state)?;
+    query.unwrap_or_default().apply(&snapshot.0, &snapshot.1)
+}
+
 #[tauri::command]
 async fn kill_process(pid: u32, state: State<'_, AppState>) -> Result<bool, String> {
     let sys = state.sys.lock().map_err(|_| "Failed to lock system state")?;
@@ -251,11 +433,69 @@ async fn kill_process(pid: u32, state: State<'_, AppState>) -> Result<bool, Stri
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if batch::requested(&args) {
+        std::process::exit(batch::main(&args));
+    }
+
     tauri::Builder::default()
         .manage(AppState::new())
+        .setup(|app| {
+            let config_path = app.path().app_config_dir()?.join("alerts.json");
+            {
+                let state = app.state::<AppState>();
+                let mut alerts = state.alerts.lock().map_err(|_| "Failed to lock alerts")?;
+                // A broken rules file should not keep the monitor from starting
+                if let Err(e) = alerts.attach(app.handle().clone(), config_path) {
+                    eprintln!("{}", e);
+                }
+            }
+            sampler::spawn(app.handle().clone())?;
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             get_processes,
-            kill_process
+            kill_process,
+            process_tree::get_process_tree,
+            process_control::signal_process,
+            process_control::signal_process_tree,
+            process_control::suspend_process,
+            process_control::resume_process,
+            scheduling::get_scheduling,
+            scheduling::set_scheduling,
+            scheduling::set_cpu_affinity,
+            threads::get_threads,
+            fds::get_open_files,
+            connections::get_process_connections,
+            memory_maps::get_memory_maps,
+            disk_io::get_top_io_processes,
+            history::get_history,
+            history::set_history_retention,
+            history::get_process_history,
+            recording::start_recording,
+            recording::stop_recording,
+            recording::get_recording_status,
+            recording::open_playback,
+            recording::close_playback,
+            recording::get_playback_status,
+            recording::seek_playback,
+            recording::pause_playback,
+            recording::resume_playback,
+            recording::set_playback_speed,
+            export::export_snapshot,
+            metrics::start_metrics_server,
+            metrics::stop_metrics_server,
+            metrics::get_metrics_status,
+            alerts::get_alert_rules,
+            alerts::save_alert_rule,
+            alerts::remove_alert_rule,
+            alerts::get_active_alerts,
+            sampler::get_sampling,
+            sampler::set_sampling,
+            delta::watch_processes,
+            delta::unwatch_processes,
+            delta::get_process_delta,
+            filter::check_process_filter
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
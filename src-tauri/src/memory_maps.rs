@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+// smaps_rollup makes the kernel walk the page tables of the whole process, so
+// each process is re-read at most this often...
+const BREAKDOWN_MAX_AGE: Duration = Duration::from_secs(10);
+// ...and only this many per sample, which spreads out the first sample and bursts of expiries
+const BREAKDOWN_READS_PER_SAMPLE: usize = 32;
+
+/// Memory figures from /proc/<pid>/smaps(_rollup), all in bytes.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MemoryBreakdown {
+    pub rss: u64,
+    /// Pages only this process maps, i.e. what killing it would free
+    pub uss: u64,
+    /// RSS with shared pages divided among the processes sharing them
+    pub pss: u64,
+    pub shared_clean: u64,
+    pub shared_dirty: u64,
+    pub private_clean: u64,
+    pub private_dirty: u64,
+    pub swap: u64,
+    pub swap_pss: u64,
+    pub anonymous: u64,
+    pub file_backed: u64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MemoryMapping {
+    /// Addresses are hex strings because they do not fit in a JS number
+    pub start: String,
+    pub end: String,
+    pub size: u64,
+    pub permissions: String,
+    pub offset: u64,
+    pub device: String,
+    pub inode: u64,
+    /// File path or pseudo name like [heap] and [stack], None for anonymous mappings
+    pub path: Option<String>,
+    #[serde(flatten)]
+    pub memory: MemoryBreakdown,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MemoryMaps {
+    pub pid: u32,
+    pub mappings: Vec<MemoryMapping>,
+    pub totals: MemoryBreakdown,
+}
+
+struct CachedBreakdown {
+    read_at: Instant,
+    breakdown: Option<MemoryBreakdown>,
+}
+
+/// Throttled smaps_rollup readings for the process list, keyed by (pid, start_time).
+/// get_memory_maps always reads fresh figures for the process being inspected.
+#[derive(Default)]
+pub struct BreakdownCache {
+    entries: HashMap<(u32, u64), CachedBreakdown>,
+    reads_left: usize,
+}
+
+impl BreakdownCache {
+    /// Resets the read budget, call once per sample.
+    pub fn begin_sample(&mut self) {
+        self.reads_left = BREAKDOWN_READS_PER_SAMPLE;
+    }
+
+    /// Returns the last reading, refreshing it first if it is stale and the budget allows.
+    pub fn get(&mut self, pid: u32, start_time: u64, now: Instant) -> Option<MemoryBreakdown> {
+        let key = (pid, start_time);
+        let stale = self
+            .entries
+            .get(&key)
+            .is_none_or(|entry| now.duration_since(entry.read_at) >= BREAKDOWN_MAX_AGE);
+        if stale && self.reads_left > 0 {
+            self.reads_left -= 1;
+            self.entries.insert(
+                key,
+                CachedBreakdown {
+                    read_at: now,
+                    breakdown: read_memory_breakdown(pid),
+                },
+            );
+        }
+        self.entries.get(&key)?.breakdown.clone()
+    }
+
+    /// Drops readings for processes that are no longer running.
+    pub fn retain(&mut self, live: &HashSet<(u32, u64)>) {
+        self.entries.retain(|key, _| live.contains(key));
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{MemoryBreakdown, MemoryMapping, MemoryMaps};
+
+    impl MemoryBreakdown {
+        // Values in smaps are reported in kB
+        fn add_field(&mut self, key: &str, kb: u64) {
+            let bytes = kb * 1024;
+            match key {
+                "Rss" => self.rss += bytes,
+                "Pss" => self.pss += bytes,
+                "Shared_Clean" => self.shared_clean += bytes,
+                "Shared_Dirty" => self.shared_dirty += bytes,
+                "Private_Clean" => self.private_clean += bytes,
+                "Private_Dirty" => self.private_dirty += bytes,
+                "Swap" => self.swap += bytes,
+                "SwapPss" => self.swap_pss += bytes,
+                "Anonymous" => self.anonymous += bytes,
+                _ => {}
+            }
+        }
+
+        fn finish(mut self) -> Self {
+            self.uss = self.private_clean + self.private_dirty;
+            self.file_backed = self.rss.saturating_sub(self.anonymous);
+            self
+        }
+
+        fn add(&mut self, other: &MemoryBreakdown) {
+            self.rss += other.rss;
+            self.uss += other.uss;
+            self.pss += other.pss;
+            self.shared_clean += other.shared_clean;
+            self.shared_dirty += other.shared_dirty;
+            self.private_clean += other.private_clean;
+            self.private_dirty += other.private_dirty;
+            self.swap += other.swap;
+            self.swap_pss += other.swap_pss;
+            self.anonymous += other.anonymous;
+            self.file_backed += other.file_backed;
+        }
+    }
+
+    // Parses "Key:   1234 kB"; returns None for lines like VmFlags
+    fn parse_field(line: &str) -> Option<(&str, u64)> {
+        let (key, rest) = line.split_once(':')?;
+        let kb = rest.trim().strip_suffix("kB")?.trim().parse().ok()?;
+        Some((key, kb))
+    }
+
+    // Parses "7f12a000-7f12b000 r-xp 00001000 08:01 131090   /usr/lib/libc.so.6"
+    fn parse_header(line: &str) -> Option<MemoryMapping> {
+        let mut parts = line.splitn(6, ' ');
+        let (start, end) = parts.next()?.split_once('-')?;
+        let permissions = parts.next()?;
+        let offset = parts.next()?;
+        let device = parts.next()?;
+        let inode = parts.next()?;
+        if permissions.len() != 4 {
+            return None;
+        }
+        let path = parts.next().map(str::trim).filter(|path| !path.is_empty());
+
+        let start_address = u64::from_str_radix(start, 16).ok()?;
+        let end_address = u64::from_str_radix(end, 16).ok()?;
+        Some(MemoryMapping {
+            start: start.to_string(),
+            end: end.to_string(),
+            size: end_address.saturating_sub(start_address),
+            permissions: permissions.to_string(),
+            offset: u64::from_str_radix(offset, 16).ok()?,
+            device: device.to_string(),
+            inode: inode.parse().ok()?,
+            path: path.map(str::to_string),
+            memory: MemoryBreakdown::default(),
+        })
+    }
+
+    fn read_proc_file(pid: u32, name: &str) -> Result<String, String> {
+        std::fs::read_to_string(format!("/proc/{}/{}", pid, name)).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => format!("No such process: {}", pid),
+            std::io::ErrorKind::PermissionDenied => format!(
+                "Permission denied: reading the memory maps of process {} requires elevated privileges",
+                pid
+            ),
+            _ => format!("Failed to read the memory maps of process {}: {}", pid, e),
+        })
+    }
+
+    /// Reads the summed figures from smaps_rollup. Returns None when the file is
+    /// missing or unreadable, e.g. for kernel threads or other users' processes.
+    pub fn read_memory_breakdown(pid: u32) -> Option<MemoryBreakdown> {
+        let rollup = read_proc_file(pid, "smaps_rollup").ok()?;
+        let mut breakdown = MemoryBreakdown::default();
+        let mut found = false;
+        for (key, kb) in rollup.lines().filter_map(parse_field) {
+            breakdown.add_field(key, kb);
+            found = true;
+        }
+        found.then(|| breakdown.finish())
+    }
+
+    pub fn read_memory_maps(pid: u32) -> Result<MemoryMaps, String> {
+        let smaps = read_proc_file(pid, "smaps")?;
+
+        let mut mappings: Vec<MemoryMapping> = Vec::new();
+        for line in smaps.lines() {
+            if let Some((key, kb)) = parse_field(line) {
+                if let Some(mapping) = mappings.last_mut() {
+                    mapping.memory.add_field(key, kb);
+                }
+            } else if let Some(mapping) = parse_header(line) {
+                mappings.push(mapping);
+            }
+        }
+
+        let mut totals = MemoryBreakdown::default();
+        for mapping in &mut mappings {
+            mapping.memory = std::mem::take(&mut mapping.memory).finish();
+            totals.add(&mapping.memory);
+        }
+
+        Ok(MemoryMaps {
+            pid,
+            mappings,
+            totals,
+        })
+    }
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn parses_kb_fields() {
+            assert_eq!(parse_field("Pss:                 120 kB"), Some(("Pss", 120)));
+            assert_eq!(parse_field("VmFlags: rd ex mr mw me"), None);
+        }
+
+        #[test]
+        fn parses_mapping_headers() {
+            let mapping = parse_header("7f12a000-7f12b000 r-xp 00001000 08:01 131090   /usr/lib/libc.so.6").unwrap();
+            assert_eq!(mapping.size, 0x1000);
+            assert_eq!(mapping.permissions, "r-xp");
+            assert_eq!(mapping.offset, 0x1000);
+            assert_eq!(mapping.inode, 131090);
+            assert_eq!(mapping.path.as_deref(), Some("/usr/lib/libc.so.6"));
+
+            let anonymous = parse_header("7ffd4000-7ffd5000 rw-p 00000000 00:00 0 ").unwrap();
+            assert_eq!(anonymous.path, None);
+            assert!(parse_header("Rss:                   4 kB").is_none());
+        }
+
+        #[test]
+        fn derives_uss_and_file_backed() {
+            let mut breakdown = MemoryBreakdown::default();
+            for (key, kb) in [("Rss", 100), ("Private_Clean", 10), ("Private_Dirty", 30), ("Anonymous", 60)] {
+                breakdown.add_field(key, kb);
+            }
+            let breakdown = breakdown.finish();
+            assert_eq!(breakdown.uss, 40 * 1024);
+            assert_eq!(breakdown.file_backed, 40 * 1024);
+        }
+
+        #[test]
+        fn reads_our_own_rollup() {
+            let breakdown = read_memory_breakdown(std::process::id()).unwrap();
+            assert!(breakdown.rss > 0);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use linux::{read_memory_breakdown, read_memory_maps};
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_memory_breakdown(_pid: u32) -> Option<MemoryBreakdown> {
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_memory_maps(_pid: u32) -> Result<MemoryMaps, String> {
+    Err("Reading memory maps is not supported on this platform".to_string())
+}
+
+#[tauri::command]
+pub async fn get_memory_maps(pid: u32) -> Result<MemoryMaps, String> {
+    tauri::async_runtime::spawn_blocking(move || read_memory_maps(pid))
+        .await
+        .map_err(|e| e.to_string())?
+}
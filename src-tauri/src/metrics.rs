@@ -0,0 +1,489 @@
+use crate::{AppState, ProcessInfo, SystemStats};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+const DEFAULT_PORT: u16 = 9184;
+const DEFAULT_TOP_N: usize = 20;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MetricsConfig {
+    pub port: u16,
+    /// How many processes, busiest CPU first, get per-process series
+    pub top_n: usize,
+    /// Process names to export instead of the top N; empty means use top N
+    pub allowlist: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct MetricsStatus {
+    pub running: bool,
+    pub address: Option<String>,
+    pub config: Option<MetricsConfig>,
+}
+
+/// The embedded /metrics listener. Dropping it stops the accept loop.
+pub struct MetricsServer {
+    address: SocketAddr,
+    config: MetricsConfig,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MetricsServer {
+    fn start(config: MetricsConfig, app: AppHandle) -> Result<Self, String> {
+        // Only ever bind to loopback, the endpoint has no authentication
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, config.port))
+            .map_err(|e| format!("Failed to listen on 127.0.0.1:{}: {}", config.port, e))?;
+        let address = listener.local_addr().map_err(|e| e.to_string())?;
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let shutdown = shutdown.clone();
+            let config = Arc::new(config.clone());
+            std::thread::Builder::new()
+                .name("metrics".to_string())
+                .spawn(move || serve(listener, config, app, &shutdown))
+                .map_err(|e| e.to_string())?
+        };
+
+        Ok(Self {
+            address,
+            config,
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    fn status(&self) -> MetricsStatus {
+        MetricsStatus {
+            running: true,
+            address: Some(self.address.to_string()),
+            config: Some(self.config.clone()),
+        }
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // accept() blocks, so wake it with a throwaway connection
+        let _ = TcpStream::connect_timeout(&self.address, Duration::from_secs(1));
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve(listener: TcpListener, config: Arc<MetricsConfig>, app: AppHandle, shutdown: &AtomicBool) {
+    for stream in listener.incoming() {
+        if shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+        let Ok(stream) = stream else {
+            continue;
+        };
+        // Each connection gets its own thread, so a slow client cannot hold up other
+        // scrapes or shutdown. A failed scrape only affects that one client.
+        let config = config.clone();
+        let app = app.clone();
+        let _ = std::thread::Builder::new()
+            .name("metrics-connection".to_string())
+            .spawn(move || handle_connection(stream, &config, &app));
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, config: &MetricsConfig, app: &AppHandle) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers; nothing in them changes the response
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim_end().is_empty() {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let path = path.split('?').next().unwrap_or_default();
+
+    let (status, body) = match (method, path) {
+        ("GET", "/metrics") => {
+            // Scrapes read the sampler's snapshot; sampling here would skew its CPU deltas
+            match app.state::<AppState>().sampler.latest() {
+                Ok(snapshot) => ("200 OK", render_metrics(&snapshot.0, &snapshot.1, config)),
+                Err(e) => ("500 Internal Server Error", format!("{}\n", e)),
+            }
+        }
+        (_, "/metrics") => ("405 Method Not Allowed", "Only GET is supported\n".to_string()),
+        _ => ("404 Not Found", "Metrics are served at /metrics\n".to_string()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        CONTENT_TYPE,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+// Label values escape backslash, double quote and newline
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn write_family<L: AsRef<str>>(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    kind: &str,
+    samples: impl IntoIterator<Item = (L, f64)>,
+) {
+    let _ = writeln!(out, "# HELP neohtop_{} {}", name, help);
+    let _ = writeln!(out, "# TYPE neohtop_{} {}", name, kind);
+    for (labels, value) in samples {
+        let labels = labels.as_ref();
+        if labels.is_empty() {
+            let _ = writeln!(out, "neohtop_{} {}", name, value);
+        } else {
+            let _ = writeln!(out, "neohtop_{}{{{}}} {}", name, labels, value);
+        }
+    }
+}
+
+fn system_gauge(out: &mut String, name: &str, help: &str, value: f64) {
+    write_family(out, name, help, "gauge", [("", value)]);
+}
+
+fn exported_processes<'a>(processes: &'a [ProcessInfo], config: &MetricsConfig) -> Vec<&'a ProcessInfo> {
+    let mut selected: Vec<&ProcessInfo> = if config.allowlist.is_empty() {
+        processes.iter().collect()
+    } else {
+        processes
+            .iter()
+            .filter(|process| config.allowlist.contains(&process.name))
+            .collect()
+    };
+    selected.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage).then(a.pid.cmp(&b.pid)));
+    if config.allowlist.is_empty() {
+        selected.truncate(config.top_n);
+    }
+    selected
+}
+
+/// Renders one scrape in the Prometheus text exposition format.
+pub fn render_metrics(processes: &[ProcessInfo], system: &SystemStats, config: &MetricsConfig) -> String {
+    let mut out = String::new();
+
+    write_family(
+        &mut out,
+        "cpu_usage_percent",
+        "CPU usage per core.",
+        "gauge",
+        system
+            .cpu_usage
+            .iter()
+            .enumerate()
+            .map(|(core, usage)| (format!("core=\"{}\"", core), f64::from(*usage))),
+    );
+    system_gauge(&mut out, "memory_total_bytes", "Total physical memory.", system.memory_total as f64);
+    system_gauge(&mut out, "memory_used_bytes", "Used physical memory.", system.memory_used as f64);
+    system_gauge(&mut out, "memory_free_bytes", "Free physical memory.", system.memory_free as f64);
+    system_gauge(&mut out, "memory_cached_bytes", "Memory used for caches.", system.memory_cached as f64);
+    system_gauge(&mut out, "uptime_seconds", "Time since boot.", system.uptime as f64);
+    write_family(
+        &mut out,
+        "load_average",
+        "System load average.",
+        "gauge",
+        ["1m", "5m", "15m"]
+            .iter()
+            .zip(system.load_avg)
+            .map(|(period, load)| (format!("period=\"{}\"", period), load)),
+    );
+    system_gauge(
+        &mut out,
+        "network_receive_bytes_per_second",
+        "Bytes received per second across all interfaces.",
+        system.network_rx_bytes as f64,
+    );
+    system_gauge(
+        &mut out,
+        "network_transmit_bytes_per_second",
+        "Bytes transmitted per second across all interfaces.",
+        system.network_tx_bytes as f64,
+    );
+    system_gauge(&mut out, "disk_total_bytes", "Capacity of the root filesystem.", system.disk_total_bytes as f64);
+    system_gauge(&mut out, "disk_used_bytes", "Used space on the root filesystem.", system.disk_used_bytes as f64);
+    system_gauge(&mut out, "disk_free_bytes", "Free space on the root filesystem.", system.disk_free_bytes as f64);
+    system_gauge(&mut out, "processes", "Number of processes.", processes.len() as f64);
+    write_family(
+        &mut out,
+        "processes_by_state",
+        "Number of processes in each scheduler state.",
+        "gauge",
+        system
+            .process_states
+            .iter()
+            .map(|(state, count)| (format!("state=\"{}\"", state.as_str()), *count as f64)),
+    );
+
+    let exported = exported_processes(processes, config);
+    let labels: Vec<String> = exported
+        .iter()
+        .map(|process| {
+            format!(
+                "pid=\"{}\",name=\"{}\",user=\"{}\"",
+                process.pid,
+                escape_label(&process.name),
+                escape_label(&process.user)
+            )
+        })
+        .collect();
+    let process_family = |out: &mut String, name: &str, help: &str, kind: &str, value: fn(&ProcessInfo) -> f64| {
+        write_family(
+            out,
+            name,
+            help,
+            kind,
+            labels.iter().zip(&exported).map(|(labels, process)| (labels, value(process))),
+        );
+    };
+
+    process_family(&mut out, "process_cpu_usage_percent", "Process CPU usage.", "gauge", |p| {
+        f64::from(p.cpu_usage)
+    });
+    process_family(&mut out, "process_resident_memory_bytes", "Process resident memory.", "gauge", |p| {
+        p.memory_usage as f64
+    });
+    process_family(&mut out, "process_virtual_memory_bytes", "Process virtual memory.", "gauge", |p| {
+        p.virtual_memory as f64
+    });
+    process_family(&mut out, "process_threads", "Number of threads in the process.", "gauge", |p| {
+        p.threads.map_or(f64::NAN, f64::from)
+    });
+    process_family(&mut out, "process_run_time_seconds", "Time since the process started.", "gauge", |p| {
+        p.run_time as f64
+    });
+    process_family(
+        &mut out,
+        "process_disk_read_bytes_per_second",
+        "Bytes read from storage per second.",
+        "gauge",
+        |p| p.disk_io.read_bytes_per_sec,
+    );
+    process_family(
+        &mut out,
+        "process_disk_write_bytes_per_second",
+        "Bytes written to storage per second.",
+        "gauge",
+        |p| p.disk_io.write_bytes_per_sec,
+    );
+    process_family(
+        &mut out,
+        "process_disk_read_bytes_total",
+        "Bytes read from storage over the process lifetime.",
+        "counter",
+        |p| p.disk_io.total_read_bytes as f64,
+    );
+    process_family(
+        &mut out,
+        "process_disk_written_bytes_total",
+        "Bytes written to storage over the process lifetime.",
+        "counter",
+        |p| p.disk_io.total_written_bytes as f64,
+    );
+
+    out
+}
+
+/// Starts the /metrics listener, replacing a running one.
+#[tauri::command]
+pub async fn start_metrics_server(
+    port: Option<u16>,
+    top_n: Option<usize>,
+    allowlist: Option<Vec<String>>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<MetricsStatus, String> {
+    let config = MetricsConfig {
+        port: port.unwrap_or(DEFAULT_PORT),
+        top_n: top_n.unwrap_or(DEFAULT_TOP_N),
+        allowlist: allowlist.unwrap_or_default(),
+    };
+    // Stop the old listener first so the same port can be reused. Dropping it joins its
+    // thread, so take it out of the lock before that.
+    let old = state.metrics.lock().map_err(|_| "Failed to lock metrics server")?.take();
+    drop(old);
+    let server = MetricsServer::start(config, app)?;
+    let status = server.status();
+    let replaced = state
+        .metrics
+        .lock()
+        .map_err(|_| "Failed to lock metrics server")?
+        .replace(server);
+    drop(replaced);
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn stop_metrics_server(state: State<'_, AppState>) -> Result<MetricsStatus, String> {
+    let old = state.metrics.lock().map_err(|_| "Failed to lock metrics server")?.take();
+    drop(old);
+    Ok(MetricsStatus {
+        running: false,
+        address: None,
+        config: None,
+    })
+}
+
+#[tauri::command]
+pub async fn get_metrics_status(state: State<'_, AppState>) -> Result<MetricsStatus, String> {
+    let metrics = state.metrics.lock().map_err(|_| "Failed to lock metrics server")?;
+    Ok(match metrics.as_ref() {
+        Some(server) => server.status(),
+        None => MetricsStatus {
+            running: false,
+            address: None,
+            config: None,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_state::ProcessState;
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(escape_label("a\\b \"c\"\nd"), "a\\\\b \\\"c\\\"\\nd");
+    }
+
+    fn process(pid: u32, name: &str, cpu_usage: f32) -> ProcessInfo {
+        let mut process = ProcessInfo {
+            pid,
+            name: name.to_string(),
+            user: "root".to_string(),
+            cpu_usage,
+            memory_usage: 2048,
+            threads: Some(3),
+            ..ProcessInfo::default()
+        };
+        process.disk_io.total_read_bytes = 100;
+        process
+    }
+
+    fn system() -> SystemStats {
+        SystemStats {
+            cpu_usage: vec![12.5, 50.0],
+            memory_total: 8192,
+            memory_used: 4096,
+            memory_free: 4096,
+            memory_cached: 0,
+            uptime: 60,
+            load_avg: [0.5, 0.25, 0.125],
+            network_rx_bytes: 10,
+            network_tx_bytes: 20,
+            disk_total_bytes: 1000,
+            disk_used_bytes: 600,
+            disk_free_bytes: 400,
+            process_states: [(ProcessState::Running, 1), (ProcessState::Sleeping, 2)].into(),
+        }
+    }
+
+    fn config(top_n: usize, allowlist: &[&str]) -> MetricsConfig {
+        MetricsConfig {
+            port: 0,
+            top_n,
+            allowlist: allowlist.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    fn lines<'a>(out: &'a str, family: &str) -> Vec<&'a str> {
+        let prefix = format!("neohtop_{}", family);
+        out.lines()
+            .filter(|line| line.strip_prefix(&prefix).is_some_and(|rest| rest.starts_with(['{', ' '])))
+            .collect()
+    }
+
+    #[test]
+    fn renders_a_snapshot() {
+        let processes = [process(1, "init", 0.5), process(20, "say \"hi\"", 90.0), process(3, "idle", 0.0)];
+        let out = render_metrics(&processes, &system(), &config(2, &[]));
+
+        assert_eq!(
+            lines(&out, "cpu_usage_percent"),
+            ["neohtop_cpu_usage_percent{core=\"0\"} 12.5", "neohtop_cpu_usage_percent{core=\"1\"} 50"]
+        );
+        assert_eq!(
+            lines(&out, "load_average")[2],
+            "neohtop_load_average{period=\"15m\"} 0.125"
+        );
+        assert_eq!(lines(&out, "memory_used_bytes"), ["neohtop_memory_used_bytes 4096"]);
+        assert_eq!(lines(&out, "processes"), ["neohtop_processes 3"]);
+        assert_eq!(
+            lines(&out, "processes_by_state"),
+            [
+                "neohtop_processes_by_state{state=\"running\"} 1",
+                "neohtop_processes_by_state{state=\"sleeping\"} 2"
+            ]
+        );
+        // The top 2 by CPU, busiest first, with escaped labels
+        assert_eq!(
+            lines(&out, "process_cpu_usage_percent"),
+            [
+                "neohtop_process_cpu_usage_percent{pid=\"20\",name=\"say \\\"hi\\\"\",user=\"root\"} 90",
+                "neohtop_process_cpu_usage_percent{pid=\"1\",name=\"init\",user=\"root\"} 0.5"
+            ]
+        );
+        assert!(out.contains("# TYPE neohtop_process_disk_read_bytes_total counter\n"));
+        assert_eq!(lines(&out, "process_disk_read_bytes_total").len(), 2);
+        assert!(lines(&out, "process_threads").iter().all(|line| line.ends_with(" 3")));
+    }
+
+    #[test]
+    fn allowlist_replaces_the_top_n() {
+        let processes = [process(1, "init", 0.5), process(2, "busy", 90.0), process(3, "idle", 0.0)];
+        let out = render_metrics(&processes, &system(), &config(1, &["init", "idle"]));
+        let pids: Vec<&str> = lines(&out, "process_resident_memory_bytes")
+            .iter()
+            .map(|line| line.split('"').nth(1).unwrap())
+            .collect();
+        assert_eq!(pids, ["1", "3"]);
+    }
+
+    #[test]
+    fn writes_help_type_and_samples() {
+        let mut out = String::new();
+        write_family(&mut out, "load_average", "System load average.", "gauge", [("period=\"1m\"", 0.5)]);
+        system_gauge(&mut out, "processes", "Number of processes.", 42.0);
+        assert_eq!(
+            out,
+            "# HELP neohtop_load_average System load average.\n\
+             # TYPE neohtop_load_average gauge\n\
+             neohtop_load_average{period=\"1m\"} 0.5\n\
+             # HELP neohtop_processes Number of processes.\n\
+             # TYPE neohtop_processes gauge\n\
+             neohtop_processes 42\n"
+        );
+    }
+}
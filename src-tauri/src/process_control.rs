@@ -0,0 +1,565 @@
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{PidExt, ProcessExt, ProcessRefreshKind, System, SystemExt};
+use tauri::State;
+
+// How often we check whether a signalled process has gone away
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+// How long we wait for a SIGKILL to take effect before giving up
+const KILL_WAIT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Hup,
+    Int,
+    Quit,
+    Kill,
+    Usr1,
+    Usr2,
+    Term,
+    Stop,
+    Cont,
+    Other(i32),
+}
+
+impl Signal {
+    /// Parses "TERM", "SIGTERM", "term" or a raw signal number like "15".
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        if let Ok(number) = value.parse::<i32>() {
+            if number <= 0 {
+                return Err(format!("Invalid signal number: {}", number));
+            }
+            return Ok(Self::from_number(number));
+        }
+
+        let upper = value.to_ascii_uppercase();
+        let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+        match name {
+            "HUP" => Ok(Signal::Hup),
+            "INT" => Ok(Signal::Int),
+            "QUIT" => Ok(Signal::Quit),
+            "KILL" => Ok(Signal::Kill),
+            "USR1" => Ok(Signal::Usr1),
+            "USR2" => Ok(Signal::Usr2),
+            "TERM" => Ok(Signal::Term),
+            "STOP" => Ok(Signal::Stop),
+            "CONT" => Ok(Signal::Cont),
+            _ => Err(format!("Unknown signal: {}", value)),
+        }
+    }
+
+    fn from_number(number: i32) -> Self {
+        [
+            Signal::Hup,
+            Signal::Int,
+            Signal::Quit,
+            Signal::Kill,
+            Signal::Usr1,
+            Signal::Usr2,
+            Signal::Term,
+            Signal::Stop,
+            Signal::Cont,
+        ]
+        .into_iter()
+        .find(|signal| signal.number() == Some(number))
+        .unwrap_or(Signal::Other(number))
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Signal::Hup => "SIGHUP".to_string(),
+            Signal::Int => "SIGINT".to_string(),
+            Signal::Quit => "SIGQUIT".to_string(),
+            Signal::Kill => "SIGKILL".to_string(),
+            Signal::Usr1 => "SIGUSR1".to_string(),
+            Signal::Usr2 => "SIGUSR2".to_string(),
+            Signal::Term => "SIGTERM".to_string(),
+            Signal::Stop => "SIGSTOP".to_string(),
+            Signal::Cont => "SIGCONT".to_string(),
+            Signal::Other(number) => format!("signal {}", number),
+        }
+    }
+
+    #[cfg(unix)]
+    fn number(&self) -> Option<i32> {
+        Some(match self {
+            Signal::Hup => libc::SIGHUP,
+            Signal::Int => libc::SIGINT,
+            Signal::Quit => libc::SIGQUIT,
+            Signal::Kill => libc::SIGKILL,
+            Signal::Usr1 => libc::SIGUSR1,
+            Signal::Usr2 => libc::SIGUSR2,
+            Signal::Term => libc::SIGTERM,
+            Signal::Stop => libc::SIGSTOP,
+            Signal::Cont => libc::SIGCONT,
+            Signal::Other(number) => *number,
+        })
+    }
+
+    // Raw numbers have no meaning outside of unix
+    #[cfg(not(unix))]
+    fn number(&self) -> Option<i32> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EscalationPolicy {
+    /// How long to wait for the process to exit before sending SIGKILL
+    #[serde(default = "default_grace_period_ms")]
+    pub grace_period_ms: u64,
+}
+
+fn default_grace_period_ms() -> u64 {
+    5000
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SignalOutcome {
+    pub pid: u32,
+    pub signals_sent: Vec<String>,
+    pub exited: bool,
+    pub terminated_by: Option<String>,
+}
+
+/// Converts a PID for the libc calls that act on one process.
+#[cfg(unix)]
+pub(crate) fn to_pid_t(pid: u32) -> Result<libc::pid_t, String> {
+    // kill(2) treats 0 and negative values as process groups and the scheduling
+    // calls treat 0 as the calling thread, never allow those
+    match libc::pid_t::try_from(pid) {
+        Ok(pid) if pid > 0 => Ok(pid),
+        _ => Err(format!("Invalid PID: {}", pid)),
+    }
+}
+
+#[cfg(unix)]
+pub fn send_signal(pid: u32, signal: Signal) -> Result<(), String> {
+    let raw_pid = to_pid_t(pid)?;
+    let number = signal.number().unwrap_or_default();
+    if unsafe { libc::kill(raw_pid, number) } == 0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "Failed to send {} to process {}: {}",
+            signal.name(),
+            pid,
+            std::io::Error::last_os_error()
+        ))
+    }
+}
+
+#[cfg(not(unix))]
+pub fn send_signal(pid: u32, signal: Signal) -> Result<(), String> {
+    let sysinfo_signal = match signal {
+        Signal::Kill => sysinfo::Signal::Kill,
+        Signal::Stop => sysinfo::Signal::Stop,
+        Signal::Cont => sysinfo::Signal::Continue,
+        _ => return Err(format!("{} is not supported on this platform", signal.name())),
+    };
+
+    let mut sys = sysinfo::System::new();
+    let pid = sysinfo::Pid::from_u32(pid);
+    if !sys.refresh_process(pid) {
+        return Err(format!("No such process: {}", pid));
+    }
+    match sys.process(pid).and_then(|process| process.kill_with(sysinfo_signal)) {
+        Some(true) => Ok(()),
+        _ => Err(format!("Failed to send {} to process {}", signal.name(), pid)),
+    }
+}
+
+/// Returns false once the process is gone or has become a zombie.
+#[cfg(unix)]
+pub fn is_alive(pid: u32) -> bool {
+    let Ok(raw_pid) = to_pid_t(pid) else {
+        return false;
+    };
+    if unsafe { libc::kill(raw_pid, 0) } != 0
+        && std::io::Error::last_os_error().raw_os_error() != Some(libc::EPERM)
+    {
+        return false;
+    }
+    !is_zombie(pid)
+}
+
+#[cfg(not(unix))]
+pub fn is_alive(pid: u32) -> bool {
+    sysinfo::System::new().refresh_process(sysinfo::Pid::from_u32(pid))
+}
+
+// A zombie has already exited, it is only waiting for its parent to reap it
+#[cfg(target_os = "linux")]
+fn is_zombie(pid: u32) -> bool {
+    crate::procfs::read_stat(pid).is_some_and(|stat| stat.state == 'Z')
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn is_zombie(_pid: u32) -> bool {
+    false
+}
+
+/// Identifies one incarnation of a PID, so that a PID reused while we wait is
+/// not mistaken for the process we signalled.
+#[cfg(target_os = "linux")]
+fn start_marker(pid: u32) -> Option<u64> {
+    crate::procfs::read_stat(pid).map(|stat| stat.start_ticks)
+}
+
+// Whole seconds only, so a PID reused within the same second goes unnoticed
+#[cfg(not(target_os = "linux"))]
+fn start_marker(pid: u32) -> Option<u64> {
+    let mut sys = System::new();
+    let pid = sysinfo::Pid::from_u32(pid);
+    if !sys.refresh_process_specifics(pid, ProcessRefreshKind::new()) {
+        return None;
+    }
+    sys.process(pid).map(|process| process.start_time())
+}
+
+/// A process we signalled, as identified when we first saw it.
+#[derive(Debug, Clone, Copy)]
+struct Target {
+    pid: u32,
+    start: Option<u64>,
+}
+
+impl Target {
+    fn new(pid: u32) -> Self {
+        Self {
+            pid,
+            start: start_marker(pid),
+        }
+    }
+
+    /// False once the process has exited, even if its PID now belongs to another one
+    fn is_running(&self) -> bool {
+        is_alive(self.pid) && start_marker(self.pid) == self.start
+    }
+}
+
+fn wait_for_exit(target: Target, timeout: Duration) -> bool {
+    wait_for_all_exited(&[target], timeout)
+}
+
+fn wait_for_all_exited(targets: &[Target], timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if targets.iter().all(|target| !target.is_running()) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(EXIT_POLL_INTERVAL);
+    }
+}
+
+/// Sends `signal`, and with an escalation policy follows up with SIGKILL
+/// if the process is still around once the grace period has passed.
+/// Blocks for up to the grace period, so run it off the async runtime.
+pub fn signal_with_escalation(
+    pid: u32,
+    signal: Signal,
+    escalation: Option<&EscalationPolicy>,
+) -> Result<SignalOutcome, String> {
+    let target = Target::new(pid);
+    send_signal(pid, signal)?;
+
+    let mut outcome = SignalOutcome {
+        pid,
+        signals_sent: vec![signal.name()],
+        exited: false,
+        terminated_by: None,
+    };
+
+    let policy = match escalation {
+        Some(policy) if signal != Signal::Kill => policy,
+        _ => {
+            if !target.is_running() {
+                outcome.exited = true;
+                outcome.terminated_by = Some(signal.name());
+            }
+            return Ok(outcome);
+        }
+    };
+
+    // A PID that changed hands during the grace period means ours has exited
+    if wait_for_exit(target, Duration::from_millis(policy.grace_period_ms)) {
+        outcome.exited = true;
+        outcome.terminated_by = Some(signal.name());
+        return Ok(outcome);
+    }
+
+    match send_signal(pid, Signal::Kill) {
+        Ok(()) => outcome.signals_sent.push(Signal::Kill.name()),
+        // It may have exited between the last check and the SIGKILL
+        Err(_) if !target.is_running() => {
+            outcome.exited = true;
+            outcome.terminated_by = Some(signal.name());
+            return Ok(outcome);
+        }
+        Err(e) => return Err(e),
+    }
+
+    if wait_for_exit(target, KILL_WAIT) {
+        outcome.exited = true;
+        outcome.terminated_by = Some(Signal::Kill.name());
+    }
+    Ok(outcome)
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeOrder {
+    /// Children are signalled before their parents, so nothing gets reparented mid-kill
+    #[default]
+    LeavesFirst,
+    /// Parents are signalled first, which stops supervisors from respawning children
+    RootFirst,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TreeSignalResult {
+    pub name: String,
+    #[serde(flatten)]
+    pub outcome: SignalOutcome,
+    pub error: Option<String>,
+    #[serde(skip)]
+    target: Target,
+}
+
+/// Walks the parent links breadth-first and returns `root` followed by
+/// all of its descendants, each (pid, name) pair appearing once.
+pub fn collect_process_tree(sys: &System, root: u32) -> Vec<(u32, String)> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (pid, process) in sys.processes() {
+        if let Some(parent) = process.parent() {
+            children.entry(parent.as_u32()).or_default().push(pid.as_u32());
+        }
+    }
+
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::from([root]);
+    let mut tree = Vec::new();
+    while let Some(pid) = queue.pop_front() {
+        // Guards against PID cycles in an inconsistent snapshot
+        if !visited.insert(pid) {
+            continue;
+        }
+        let Some(process) = sys.process(sysinfo::Pid::from_u32(pid)) else {
+            continue;
+        };
+        tree.push((pid, process.name().to_string()));
+        if let Some(kids) = children.get(&pid) {
+            queue.extend(kids.iter().copied());
+        }
+    }
+    tree
+}
+
+/// Signals every process of a tree in the given order. With an escalation
+/// policy the whole tree shares one grace period, after which survivors
+/// receive SIGKILL in the same order.
+pub fn signal_tree(
+    tree: Vec<(u32, String)>,
+    signal: Signal,
+    order: TreeOrder,
+    escalation: Option<&EscalationPolicy>,
+) -> Vec<TreeSignalResult> {
+    let mut results: Vec<TreeSignalResult> = tree
+        .into_iter()
+        .map(|(pid, name)| TreeSignalResult {
+            target: Target::new(pid),
+            name,
+            outcome: SignalOutcome {
+                pid,
+                signals_sent: Vec::new(),
+                exited: false,
+                terminated_by: None,
+            },
+            error: None,
+        })
+        .collect();
+
+    // Breadth-first order puts every parent before its children
+    if let TreeOrder::LeavesFirst = order {
+        results.reverse();
+    }
+
+    send_to_results(&mut results, signal);
+
+    if let Some(policy) = escalation.filter(|_| signal != Signal::Kill) {
+        let signalled = signalled_targets(&results);
+        let grace_period = Duration::from_millis(policy.grace_period_ms);
+        if !wait_for_all_exited(&signalled, grace_period) {
+            send_to_results(&mut results, Signal::Kill);
+            wait_for_all_exited(&signalled_targets(&results), KILL_WAIT);
+        }
+    }
+
+    for result in &mut results {
+        if let Some(last) = result.outcome.signals_sent.last() {
+            if !result.target.is_running() {
+                result.outcome.exited = true;
+                result.outcome.terminated_by = Some(last.clone());
+            }
+        }
+    }
+    results
+}
+
+fn send_to_results(results: &mut [TreeSignalResult], signal: Signal) {
+    for result in results.iter_mut() {
+        if result.error.is_some() {
+            continue;
+        }
+        // Only signal processes that are still running, and never a reused PID
+        if !result.target.is_running() {
+            continue;
+        }
+        match send_signal(result.outcome.pid, signal) {
+            Ok(()) => result.outcome.signals_sent.push(signal.name()),
+            // Exiting between the walk and the signal is not a failure
+            Err(_) if !result.target.is_running() => {}
+            Err(e) => result.error = Some(e),
+        }
+    }
+}
+
+fn signalled_targets(results: &[TreeSignalResult]) -> Vec<Target> {
+    results
+        .iter()
+        .filter(|result| !result.outcome.signals_sent.is_empty())
+        .map(|result| result.target)
+        .collect()
+}
+
+fn parse_signal_or_term(signal: Option<String>) -> Result<Signal, String> {
+    match signal {
+        Some(signal) => Signal::parse(&signal),
+        None => Ok(Signal::Term),
+    }
+}
+
+#[tauri::command]
+pub async fn signal_process(
+    pid: u32,
+    signal: Option<String>,
+    escalation: Option<EscalationPolicy>,
+) -> Result<SignalOutcome, String> {
+    let signal = parse_signal_or_term(signal)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        signal_with_escalation(pid, signal, escalation.as_ref())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn signal_process_tree(
+    pid: u32,
+    signal: Option<String>,
+    order: Option<TreeOrder>,
+    escalation: Option<EscalationPolicy>,
+    state: State<'_, AppState>,
+) -> Result<Vec<TreeSignalResult>, String> {
+    let signal = parse_signal_or_term(signal)?;
+
+    let tree = lookup_targets(&state, pid, true)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        signal_tree(tree, signal, order.unwrap_or_default(), escalation.as_ref())
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Freezes a process, or its whole subtree, with SIGSTOP.
+#[tauri::command]
+pub async fn suspend_process(
+    pid: u32,
+    include_children: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<TreeSignalResult>, String> {
+    let targets = lookup_targets(&state, pid, include_children.unwrap_or(false))?;
+    // Stop parents first so they cannot react to their children freezing
+    Ok(signal_tree(targets, Signal::Stop, TreeOrder::RootFirst, None))
+}
+
+/// Resumes a process, or its whole subtree, with SIGCONT.
+#[tauri::command]
+pub async fn resume_process(
+    pid: u32,
+    include_children: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<TreeSignalResult>, String> {
+    let targets = lookup_targets(&state, pid, include_children.unwrap_or(false))?;
+    Ok(signal_tree(targets, Signal::Cont, TreeOrder::LeavesFirst, None))
+}
+
+fn lookup_targets(
+    state: &State<'_, AppState>,
+    pid: u32,
+    include_children: bool,
+) -> Result<Vec<(u32, String)>, String> {
+    let mut sys = state.sys.lock().map_err(|_| "Failed to lock system state")?;
+    // Pick up children spawned since the last sample without touching CPU deltas
+    sys.refresh_processes_specifics(ProcessRefreshKind::new());
+
+    let mut targets = collect_process_tree(&sys, pid);
+    if targets.is_empty() {
+        return Err(format!("No such process: {}", pid));
+    }
+    if !include_children {
+        targets.truncate(1);
+    }
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_signal_names_and_numbers() {
+        assert_eq!(Signal::parse("TERM"), Ok(Signal::Term));
+        assert_eq!(Signal::parse(" sigkill "), Ok(Signal::Kill));
+        assert_eq!(Signal::parse("usr1"), Ok(Signal::Usr1));
+        assert!(Signal::parse("BOGUS").is_err());
+        assert!(Signal::parse("0").is_err());
+        assert!(Signal::parse("-9").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parses_raw_numbers_to_known_signals() {
+        assert_eq!(Signal::parse("15"), Ok(Signal::Term));
+        assert_eq!(Signal::parse("9"), Ok(Signal::Kill));
+        assert_eq!(Signal::parse("64"), Ok(Signal::Other(64)));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn escalates_to_sigkill_when_term_is_ignored() {
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "trap '' TERM; while :; do sleep 0.05; done"])
+            .spawn()
+            .unwrap();
+        // Give the shell time to install its trap
+        thread::sleep(Duration::from_millis(200));
+
+        let policy = EscalationPolicy { grace_period_ms: 200 };
+        let outcome = signal_with_escalation(child.id(), Signal::Term, Some(&policy)).unwrap();
+        let _ = child.wait();
+
+        assert_eq!(outcome.signals_sent, ["SIGTERM", "SIGKILL"]);
+        assert!(outcome.exited);
+        assert_eq!(outcome.terminated_by.as_deref(), Some("SIGKILL"));
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use sysinfo::ProcessStatus;
+
+/// Scheduler state of a process, one variant per state sysinfo can report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessState {
+    Running,
+    Sleeping,
+    Idle,
+    /// Uninterruptible sleep, usually waiting on I/O ("D")
+    DiskSleep,
+    Stopped,
+    /// Stopped by a debugger ("t")
+    Tracing,
+    Zombie,
+    Dead,
+    Wakekill,
+    Waking,
+    Parked,
+    /// Waiting on a lock (FreeBSD)
+    LockBlocked,
+    Unknown,
+}
+
+impl From<ProcessStatus> for ProcessState {
+    fn from(status: ProcessStatus) -> Self {
+        match status {
+            ProcessStatus::Run => ProcessState::Running,
+            ProcessStatus::Sleep => ProcessState::Sleeping,
+            ProcessStatus::Idle => ProcessState::Idle,
+            ProcessStatus::UninterruptibleDiskSleep => ProcessState::DiskSleep,
+            ProcessStatus::Stop => ProcessState::Stopped,
+            ProcessStatus::Tracing => ProcessState::Tracing,
+            ProcessStatus::Zombie => ProcessState::Zombie,
+            ProcessStatus::Dead => ProcessState::Dead,
+            ProcessStatus::Wakekill => ProcessState::Wakekill,
+            ProcessStatus::Waking => ProcessState::Waking,
+            ProcessStatus::Parked => ProcessState::Parked,
+            ProcessStatus::LockBlocked => ProcessState::LockBlocked,
+            ProcessStatus::Unknown(_) => ProcessState::Unknown,
+        }
+    }
+}
+
+impl ProcessState {
+    /// Maps a state letter from /proc/<pid>/stat the same way sysinfo does.
+    #[cfg(target_os = "linux")]
+    pub fn from_letter(letter: char) -> Self {
+        ProcessState::from(ProcessStatus::from(letter))
+    }
+
+    /// Name used in the `status` column and the status filter
+    pub fn label(self) -> &'static str {
+        match self {
+            ProcessState::Running => "Running",
+            ProcessState::Sleeping => "Sleeping",
+            ProcessState::Idle => "Idle",
+            ProcessState::DiskSleep => "Disk Sleep",
+            ProcessState::Stopped => "Stopped",
+            ProcessState::Tracing => "Tracing",
+            ProcessState::Zombie => "Zombie",
+            ProcessState::Dead => "Dead",
+            ProcessState::Wakekill => "Wakekill",
+            ProcessState::Waking => "Waking",
+            ProcessState::Parked => "Parked",
+            ProcessState::LockBlocked => "Locked",
+            ProcessState::Unknown => "Unknown",
+        }
+    }
+
+    /// Same as the serialized name
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProcessState::Running => "running",
+            ProcessState::Sleeping => "sleeping",
+            ProcessState::Idle => "idle",
+            ProcessState::DiskSleep => "disk_sleep",
+            ProcessState::Stopped => "stopped",
+            ProcessState::Tracing => "tracing",
+            ProcessState::Zombie => "zombie",
+            ProcessState::Dead => "dead",
+            ProcessState::Wakekill => "wakekill",
+            ProcessState::Waking => "waking",
+            ProcessState::Parked => "parked",
+            ProcessState::LockBlocked => "lock_blocked",
+            ProcessState::Unknown => "unknown",
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_stat_letters() {
+        let states = [
+            ('R', ProcessState::Running),
+            ('S', ProcessState::Sleeping),
+            ('I', ProcessState::Idle),
+            ('D', ProcessState::DiskSleep),
+            ('T', ProcessState::Stopped),
+            ('t', ProcessState::Tracing),
+            ('Z', ProcessState::Zombie),
+            ('X', ProcessState::Dead),
+            ('x', ProcessState::Dead),
+            ('K', ProcessState::Wakekill),
+            ('W', ProcessState::Waking),
+            ('P', ProcessState::Parked),
+            ('?', ProcessState::Unknown),
+        ];
+        for (letter, state) in states {
+            assert_eq!(ProcessState::from_letter(letter), state, "{}", letter);
+        }
+    }
+}
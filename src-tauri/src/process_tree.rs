@@ -0,0 +1,200 @@
+use crate::{current_snapshot, AppState, ProcessInfo};
+use std::collections::HashMap;
+use tauri::State;
+
+const INIT_PID: u32 = 1;
+
+#[derive(serde::Serialize, Clone, Debug, Default)]
+pub struct SubtreeTotals {
+    pub cpu_usage: f32,
+    pub memory_usage: u64,
+    pub virtual_memory: u64,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    pub total_read_bytes: u64,
+    pub total_written_bytes: u64,
+    pub descendant_count: usize,
+}
+
+impl SubtreeTotals {
+    fn for_process(process: &ProcessInfo) -> Self {
+        Self {
+            cpu_usage: process.cpu_usage,
+            memory_usage: process.memory_usage,
+            virtual_memory: process.virtual_memory,
+            read_bytes_per_sec: process.disk_io.read_bytes_per_sec,
+            write_bytes_per_sec: process.disk_io.write_bytes_per_sec,
+            total_read_bytes: process.disk_io.total_read_bytes,
+            total_written_bytes: process.disk_io.total_written_bytes,
+            descendant_count: 0,
+        }
+    }
+
+    fn add_child(&mut self, child: &SubtreeTotals) {
+        self.cpu_usage += child.cpu_usage;
+        self.memory_usage += child.memory_usage;
+        self.virtual_memory += child.virtual_memory;
+        self.read_bytes_per_sec += child.read_bytes_per_sec;
+        self.write_bytes_per_sec += child.write_bytes_per_sec;
+        self.total_read_bytes += child.total_read_bytes;
+        self.total_written_bytes += child.total_written_bytes;
+        self.descendant_count += child.descendant_count + 1;
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct ProcessTreeNode {
+    #[serde(flatten)]
+    pub process: ProcessInfo,
+    /// Totals for this process and everything below it
+    pub subtree: SubtreeTotals,
+    pub children: Vec<ProcessTreeNode>,
+}
+
+// Picks the node a process hangs off in the tree, or None for a root
+fn effective_parent(pid: u32, ppid: u32, processes: &HashMap<u32, ProcessInfo>) -> Option<u32> {
+    if ppid == 0 || ppid == pid {
+        return None;
+    }
+    if processes.contains_key(&ppid) {
+        return Some(ppid);
+    }
+    // The parent exited after we listed it, the kernel hands orphans to init
+    if pid != INIT_PID && processes.contains_key(&INIT_PID) {
+        return Some(INIT_PID);
+    }
+    None
+}
+
+/// Nests a flat process list by parent PID. Every process appears exactly
+/// once, even when the snapshot contains parent cycles.
+pub fn build_process_tree(processes: Vec<ProcessInfo>) -> Vec<ProcessTreeNode> {
+    let mut remaining: HashMap<u32, ProcessInfo> =
+        processes.into_iter().map(|process| (process.pid, process)).collect();
+
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut roots = Vec::new();
+    for (pid, process) in &remaining {
+        match effective_parent(*pid, process.ppid, &remaining) {
+            Some(parent) => children.entry(parent).or_default().push(*pid),
+            None => roots.push(*pid),
+        }
+    }
+    roots.sort_unstable();
+    for kids in children.values_mut() {
+        kids.sort_unstable();
+    }
+
+    let mut tree: Vec<ProcessTreeNode> = roots
+        .into_iter()
+        .filter_map(|pid| build_node(pid, &mut remaining, &children))
+        .collect();
+
+    // Anything left over is only reachable through a cycle, so break it at the lowest PID
+    while let Some(pid) = remaining.keys().min().copied() {
+        if let Some(node) = build_node(pid, &mut remaining, &children) {
+            tree.push(node);
+        }
+    }
+
+    tree
+}
+
+fn build_node(
+    pid: u32,
+    remaining: &mut HashMap<u32, ProcessInfo>,
+    children: &HashMap<u32, Vec<u32>>,
+) -> Option<ProcessTreeNode> {
+    // Taking the process out of the map is what stops cycles from recursing forever
+    let process = remaining.remove(&pid)?;
+    let mut subtree = SubtreeTotals::for_process(&process);
+
+    let child_nodes: Vec<ProcessTreeNode> = children
+        .get(&pid)
+        .into_iter()
+        .flatten()
+        .filter_map(|child| build_node(*child, remaining, children))
+        .collect();
+    for child in &child_nodes {
+        subtree.add_child(&child.subtree);
+    }
+
+    Some(ProcessTreeNode {
+        process,
+        subtree,
+        children: child_nodes,
+    })
+}
+
+/// Builds the tree from the snapshot the table shows, so it follows playback too.
+#[tauri::command]
+pub async fn get_process_tree(state: State<'_, AppState>) -> Result<Vec<ProcessTreeNode>, String> {
+    // Every process ends up in the tree, so this is the one place that copies them all
+    let snapshot = current_snapshot(&state)?;
+    Ok(build_process_tree(snapshot.0.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, ppid: u32) -> ProcessInfo {
+        let mut process = ProcessInfo {
+            pid,
+            ppid,
+            cpu_usage: 1.0,
+            memory_usage: 10,
+            ..ProcessInfo::default()
+        };
+        process.disk_io.read_bytes_per_sec = 2.0;
+        process.disk_io.total_written_bytes = 100;
+        process
+    }
+
+    // (pid, children) pairs in tree order, so shape and membership are checked at once
+    fn shape(nodes: &[ProcessTreeNode]) -> Vec<(u32, Vec<u32>)> {
+        let mut shape = Vec::new();
+        for node in nodes {
+            let children = node.children.iter().map(|child| child.process.pid).collect();
+            shape.push((node.process.pid, children));
+            shape.extend(self::shape(&node.children));
+        }
+        shape
+    }
+
+    #[test]
+    fn orphans_move_to_init() {
+        let tree = build_process_tree(vec![process(1, 0), process(5, 1), process(7, 4)]);
+        assert_eq!(shape(&tree), [(1, vec![5, 7]), (5, vec![]), (7, vec![])]);
+
+        // Without init an orphan is a root of its own
+        let tree = build_process_tree(vec![process(5, 0), process(7, 4)]);
+        assert_eq!(shape(&tree), [(5, vec![]), (7, vec![])]);
+    }
+
+    #[test]
+    fn self_parents_are_roots() {
+        let tree = build_process_tree(vec![process(1, 0), process(3, 3)]);
+        assert_eq!(shape(&tree), [(1, vec![]), (3, vec![])]);
+    }
+
+    #[test]
+    fn cycles_break_at_the_lowest_pid() {
+        let tree = build_process_tree(vec![process(4, 3), process(3, 4)]);
+        assert_eq!(shape(&tree), [(3, vec![4]), (4, vec![])]);
+
+        let tree = build_process_tree(vec![process(1, 0), process(6, 8), process(7, 6), process(8, 7)]);
+        assert_eq!(shape(&tree), [(1, vec![]), (6, vec![7]), (7, vec![8]), (8, vec![])]);
+    }
+
+    #[test]
+    fn totals_cover_the_whole_subtree() {
+        let tree = build_process_tree(vec![process(1, 0), process(2, 1), process(3, 2), process(4, 1)]);
+        let root = &tree[0].subtree;
+        assert_eq!(root.descendant_count, 3);
+        assert_eq!((root.cpu_usage, root.memory_usage), (4.0, 40));
+        assert_eq!((root.read_bytes_per_sec, root.total_written_bytes), (8.0, 400));
+        let middle = &tree[0].children[0].subtree;
+        assert_eq!((middle.descendant_count, middle.memory_usage), (1, 20));
+    }
+}
@@ -0,0 +1,178 @@
+//! Parsers for the per-process files under /proc. Elsewhere the readers return None.
+
+/// The fields of /proc/<pid>/stat we use, see proc(5).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stat {
+    /// The kernel's name for the process, at most 15 bytes, replaced on exec
+    pub comm: String,
+    /// State letter such as 'R', 'S' or 'Z'
+    pub state: char,
+    /// Every thread, the main one included
+    pub num_threads: u32,
+    /// Clock ticks since boot, finer than the whole seconds sysinfo reports
+    pub start_ticks: u64,
+    /// Clock ticks spent in user and kernel mode
+    pub utime: u64,
+    pub stime: u64,
+    /// CPU the task last ran on
+    pub processor: Option<u32>,
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn parse_stat(contents: &str) -> Option<Stat> {
+    // The name in parentheses may itself contain spaces and ')', so split after the last one
+    let (pid_and_comm, rest) = contents.rsplit_once(')')?;
+    let (_, comm) = pid_and_comm.split_once('(')?;
+    // fields[0] is field 3 (state) in proc(5), so field N lives at fields[N - 3]
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    Some(Stat {
+        comm: comm.to_string(),
+        state: fields.first()?.chars().next()?,
+        num_threads: fields.get(17)?.parse().ok()?,
+        start_ticks: fields.get(19)?.parse().ok()?,
+        utime: fields.get(11)?.parse().ok()?,
+        stime: fields.get(12)?.parse().ok()?,
+        // Very old kernels stop before this field
+        processor: fields.get(36).and_then(|cpu| cpu.parse().ok()),
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_stat(pid: u32) -> Option<Stat> {
+    parse_stat(&std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?)
+}
+
+/// Same as read_stat for one thread of a process.
+#[cfg(target_os = "linux")]
+pub fn read_task_stat(pid: u32, tid: u32) -> Option<Stat> {
+    parse_stat(&std::fs::read_to_string(format!("/proc/{}/task/{}/stat", pid, tid)).ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_stat(_pid: u32) -> Option<Stat> {
+    None
+}
+
+/// User and group IDs from /proc/<pid>/status, which sysinfo only reads once per process.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Status {
+    /// Real, effective and saved
+    pub uids: (u32, u32, u32),
+    pub gids: (u32, u32, u32),
+    pub groups: Vec<u32>,
+}
+
+// Parses "\t1000\t1000\t1000\t1000" into (real, effective, saved), ignoring the filesystem ID
+fn parse_id_triple(value: &str) -> Option<(u32, u32, u32)> {
+    let mut ids = value.split_whitespace().map(|id| id.parse::<u32>());
+    Some((ids.next()?.ok()?, ids.next()?.ok()?, ids.next()?.ok()?))
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn parse_status(contents: &str) -> Option<Status> {
+    let mut uids = None;
+    let mut gids = None;
+    let mut groups = Vec::new();
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key {
+            "Uid" => uids = parse_id_triple(value),
+            "Gid" => gids = parse_id_triple(value),
+            "Groups" => groups = value.split_whitespace().filter_map(|gid| gid.parse().ok()).collect(),
+            _ => {}
+        }
+    }
+    Some(Status {
+        uids: uids?,
+        gids: gids?,
+        groups,
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_status(pid: u32) -> Option<Status> {
+    parse_status(&std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_status(_pid: u32) -> Option<Status> {
+    None
+}
+
+/// The current argv, which unlike sysinfo's copy follows exec. Empty for kernel threads
+/// and zombies.
+#[cfg(target_os = "linux")]
+pub fn read_cmdline(pid: u32) -> Option<Vec<String>> {
+    let contents = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    if contents.is_empty() {
+        return Some(Vec::new());
+    }
+    // Arguments are NUL terminated rather than separated
+    let args = contents.strip_suffix(b"\0").unwrap_or(&contents);
+    Some(
+        args.split(|byte| *byte == 0)
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect(),
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_cmdline(_pid: u32) -> Option<Vec<String>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STAT: &str = "1234 (tmux: server) S 1 1234 1234 0 -1 4194560 3096 0 0 0 \
+                        81 36 0 0 20 0 1 0 4406 9428992 1102 18446744073709551615 1 1 0 0 0 0 0 \
+                        3674112 1115202055 0 0 0 17 2 0 0 0 0 0 0 0 0 0 0 0 0 0";
+
+    #[test]
+    fn parses_state_times_and_processor() {
+        let expected = Stat {
+            comm: "tmux: server".to_string(),
+            state: 'S',
+            num_threads: 1,
+            start_ticks: 4406,
+            utime: 81,
+            stime: 36,
+            processor: Some(2),
+        };
+        assert_eq!(parse_stat(STAT), Some(expected));
+    }
+
+    #[test]
+    fn name_may_contain_parentheses() {
+        let stat = STAT.replace("(tmux: server)", "(a) Z (b)");
+        let stat = parse_stat(&stat).unwrap();
+        assert_eq!((stat.comm.as_str(), stat.state), ("a) Z (b", 'S'));
+    }
+
+    #[test]
+    fn rejects_truncated_stat() {
+        assert_eq!(parse_stat("1234 (sh) R 1 2"), None);
+    }
+
+    #[test]
+    fn parses_ids_and_groups() {
+        let status = "Name:\tsu\nUid:\t1000\t0\t0\t0\nGid:\t100\t100\t100\t100\nGroups:\t4 27 \n";
+        let expected = Status {
+            uids: (1000, 0, 0),
+            gids: (100, 100, 100),
+            groups: vec![4, 27],
+        };
+        assert_eq!(parse_status(status), Some(expected));
+        assert_eq!(parse_status("Name:\tsu\n"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn reads_own_cmdline() {
+        let args: Vec<String> = std::env::args().collect();
+        assert_eq!(read_cmdline(std::process::id()), Some(args));
+    }
+}
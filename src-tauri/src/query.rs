@@ -0,0 +1,259 @@
+use crate::filter::Filter;
+use crate::{ProcessInfo, SystemStats};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    Pid,
+    Ppid,
+    Name,
+    CpuUsage,
+    MemoryUsage,
+    Status,
+    User,
+    Command,
+    Root,
+    Environ,
+    Threads,
+    VirtualMemory,
+    StartTime,
+    RunTime,
+    /// Read plus written bytes since the previous refresh
+    DiskUsage,
+    SessionId,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Which page of which processes get_processes returns. Every part is optional.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ProcessQuery {
+    pub sort_by: Option<SortKey>,
+    pub sort_direction: SortDirection,
+    /// Exact status label such as "Running"; "all" is the same as none
+    pub status: Option<String>,
+    pub user: Option<String>,
+    /// Case-insensitive substring of the name or command line
+    pub name: Option<String>,
+    /// The process table's search box: comma separated terms, any of which may match the
+    /// name as a case-insensitive substring or regex, the command line or the PID
+    pub search: Option<String>,
+    /// Command lines to list ahead of the rest, each group in the requested order
+    pub pinned: Vec<String>,
+    /// Filter expression, see the filter module for the syntax
+    pub filter: Option<String>,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct ProcessPage {
+    pub processes: Vec<ProcessInfo>,
+    /// Number of processes matching the filters, before offset and limit
+    pub total: usize,
+    pub offset: usize,
+    pub system: SystemStats,
+}
+
+fn compare(key: SortKey, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
+    match key {
+        SortKey::Pid => a.pid.cmp(&b.pid),
+        SortKey::Ppid => a.ppid.cmp(&b.ppid),
+        SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        SortKey::CpuUsage => a.cpu_usage.total_cmp(&b.cpu_usage),
+        SortKey::MemoryUsage => a.memory_usage.cmp(&b.memory_usage),
+        SortKey::Status => a.status.cmp(&b.status),
+        SortKey::User => a.user.cmp(&b.user),
+        SortKey::Command => a.command.cmp(&b.command),
+        SortKey::Root => a.root.cmp(&b.root),
+        SortKey::Environ => a.environ.cmp(&b.environ),
+        SortKey::Threads => a.threads.cmp(&b.threads),
+        SortKey::VirtualMemory => a.virtual_memory.cmp(&b.virtual_memory),
+        SortKey::StartTime => a.start_time.cmp(&b.start_time),
+        SortKey::RunTime => a.run_time.cmp(&b.run_time),
+        SortKey::DiskUsage => (a.disk_usage.0 + a.disk_usage.1).cmp(&(b.disk_usage.0 + b.disk_usage.1)),
+        SortKey::SessionId => a.session_id.cmp(&b.session_id),
+    }
+}
+
+struct SearchTerm {
+    lowercase: String,
+    // Terms that are not valid regexes still match as substrings
+    pattern: Option<Regex>,
+}
+
+impl SearchTerm {
+    fn parse(search: &str) -> Vec<SearchTerm> {
+        search
+            .split(',')
+            .map(str::trim)
+            .filter(|term| !term.is_empty())
+            .map(|term| SearchTerm {
+                lowercase: term.to_lowercase(),
+                pattern: RegexBuilder::new(term).case_insensitive(true).build().ok(),
+            })
+            .collect()
+    }
+
+    fn matches(&self, process: &ProcessInfo) -> bool {
+        process.name.to_lowercase().contains(&self.lowercase)
+            || self.pattern.as_ref().is_some_and(|pattern| pattern.is_match(&process.name))
+            || process.command.to_lowercase().contains(&self.lowercase)
+            || process.pid.to_string().contains(&self.lowercase)
+    }
+}
+
+impl ProcessQuery {
+    fn matches(&self, process: &ProcessInfo, name: Option<&str>, search: &[SearchTerm]) -> bool {
+        let status_matches = match self.status.as_deref() {
+            None | Some("all") => true,
+            Some(status) => process.status == status,
+        };
+        let user_matches = self.user.as_ref().is_none_or(|user| process.user == *user);
+        let name_matches = name.is_none_or(|name| {
+            process.name.to_lowercase().contains(name) || process.command.to_lowercase().contains(name)
+        });
+        let search_matches = search.is_empty() || search.iter().any(|term| term.matches(process));
+        status_matches && user_matches && name_matches && search_matches
+    }
+
+    /// Filters, sorts and pages one snapshot, returning the page and the number of matches.
+    /// Fails only if the filter expression does not parse.
+    pub fn select<'a>(&self, processes: &'a [ProcessInfo]) -> Result<(Vec<&'a ProcessInfo>, usize), String> {
+        let name = self.name.as_ref().map(|name| name.to_lowercase());
+        let search = self.search.as_deref().map(SearchTerm::parse).unwrap_or_default();
+        let filter = self
+            .filter
+            .as_deref()
+            .filter(|filter| !filter.trim().is_empty())
+            .map(Filter::parse)
+            .transpose()?;
+        let mut processes: Vec<&ProcessInfo> = processes
+            .iter()
+            .filter(|process| self.matches(process, name.as_deref(), &search))
+            .filter(|process| filter.as_ref().is_none_or(|filter| filter.matches(process)))
+            .collect();
+
+        let pinned = |process: &&ProcessInfo| self.pinned.contains(&process.command);
+        processes.sort_by(|a, b| {
+            let ordering = match self.sort_by {
+                Some(key) => match self.sort_direction {
+                    SortDirection::Asc => compare(key, a, b),
+                    SortDirection::Desc => compare(key, b, a),
+                },
+                // The process table has no order of its own, so PID keeps offsets stable
+                None => Ordering::Equal,
+            };
+            // Ties fall back to the PID so pages do not shuffle between calls
+            pinned(b).cmp(&pinned(a)).then(ordering).then(a.pid.cmp(&b.pid))
+        });
+
+        let total = processes.len();
+        let page = processes
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect();
+        Ok((page, total))
+    }
+
+    /// Like select, copying only the rows on the page.
+    pub fn apply(&self, processes: &[ProcessInfo], system: &SystemStats) -> Result<ProcessPage, String> {
+        let (page, total) = self.select(processes)?;
+        Ok(ProcessPage {
+            processes: page.into_iter().cloned().collect(),
+            total,
+            offset: self.offset,
+            system: system.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, name: &str, cpu_usage: f32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            command: format!("/usr/bin/{}", name),
+            cpu_usage,
+            status: "Running".to_string(),
+            ..ProcessInfo::default()
+        }
+    }
+
+    fn system() -> SystemStats {
+        serde_json::from_value(serde_json::json!({
+            "cpu_usage": [], "memory_total": 0, "memory_used": 0, "memory_free": 0,
+            "memory_cached": 0, "uptime": 0, "load_avg": [0.0, 0.0, 0.0],
+            "network_rx_bytes": 0, "network_tx_bytes": 0,
+            "disk_total_bytes": 0, "disk_used_bytes": 0, "disk_free_bytes": 0
+        }))
+        .unwrap()
+    }
+
+    fn pids(query: ProcessQuery) -> (Vec<u32>, usize) {
+        let processes = vec![
+            process(30, "postgres", 5.0),
+            process(10, "bash", 20.0),
+            process(20, "firefox", 20.0),
+            process(40, "Xorg", 1.0),
+        ];
+        let page = query.apply(&processes, &system()).unwrap();
+        (page.processes.iter().map(|process| process.pid).collect(), page.total)
+    }
+
+    #[test]
+    fn sorts_with_pid_tie_break_and_pages() {
+        let query = ProcessQuery {
+            sort_by: Some(SortKey::CpuUsage),
+            offset: 1,
+            limit: Some(2),
+            ..ProcessQuery::default()
+        };
+        assert_eq!(pids(query), (vec![20, 30], 4));
+        assert_eq!(pids(ProcessQuery::default()).0, [10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn pinned_commands_come_first() {
+        let query = ProcessQuery {
+            sort_by: Some(SortKey::Name),
+            sort_direction: SortDirection::Asc,
+            pinned: vec!["/usr/bin/postgres".to_string(), "/usr/bin/Xorg".to_string()],
+            ..ProcessQuery::default()
+        };
+        assert_eq!(pids(query).0, [30, 40, 10, 20]);
+    }
+
+    #[test]
+    fn search_terms_match_name_regex_command_or_pid() {
+        let search = |search: &str| {
+            pids(ProcessQuery {
+                search: Some(search.to_string()),
+                ..ProcessQuery::default()
+            })
+            .0
+        };
+        assert_eq!(search("FIRE"), [20]);
+        assert_eq!(search("^x.*g$"), [40]);
+        assert_eq!(search("usr/bin/bash"), [10]);
+        assert_eq!(search("3"), [30]);
+        assert_eq!(search("bash, post"), [10, 30]);
+        // An invalid regex still matches as plain text
+        assert_eq!(search("fire("), Vec::<u32>::new());
+        assert_eq!(search(" , ").len(), 4);
+    }
+}
@@ -0,0 +1,606 @@
+//! Session recording and playback.
+//!
+//! A recording is a header followed by records, appended as samples are taken:
+//!
+//! ```text
+//! header: b"NEOHTOP\0" | version: u16 LE
+//! record: timestamp_ms: u64 LE | payload_len: u32 LE | kind: u8 | payload (MessagePack)
+//! ```
+//!
+//! A snapshot record holds every process and the system stats. Environment
+//! variables are not recorded and command lines are left empty; instead a
+//! command record is written whenever a process starts or execs, mapping its
+//! (pid, start_time) to the command line. Payloads encode field names, so newer
+//! builds can still read records written before a field was added.
+//!
+//! A torn record at the end of the file (e.g. after a crash) is ignored on
+//! playback and cut off before appending.
+
+use crate::sampler::Snapshot;
+use crate::{AppState, ProcessInfo, SystemStats};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::State;
+
+const MAGIC: &[u8; 8] = b"NEOHTOP\0";
+const FORMAT_VERSION: u16 = 1;
+const HEADER_LEN: u64 = 10;
+const RECORD_HEADER_LEN: u64 = 13;
+const MAX_PLAYBACK_SPEED: f64 = 100.0;
+
+const SNAPSHOT_RECORD: u8 = 0;
+const COMMAND_RECORD: u8 = 1;
+
+#[derive(Serialize)]
+struct FrameRef<'a> {
+    processes: &'a [ProcessInfo],
+    system: &'a SystemStats,
+}
+
+#[derive(Deserialize)]
+struct Frame {
+    processes: Vec<ProcessInfo>,
+    system: SystemStats,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CommandEntry {
+    pid: u32,
+    start_time: u64,
+    command: String,
+}
+
+pub struct Recorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    frames_written: u64,
+    // Command lines already in the file for this session, by (pid, start_time)
+    commands: HashMap<(u32, u64), String>,
+}
+
+impl Recorder {
+    /// Opens `path` for appending, writing the header if the file is new.
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+
+        let len = file.metadata().map_err(|e| e.to_string())?.len();
+        if len == 0 {
+            let mut header = Vec::with_capacity(HEADER_LEN as usize);
+            header.extend_from_slice(MAGIC);
+            header.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+            file.write_all(&header).map_err(|e| e.to_string())?;
+        } else {
+            read_header(&mut file)?;
+            // Records appended after a torn one would start at the wrong offset
+            let (_, end) = scan_records(&mut BufReader::new(&file), len)?;
+            if end < len {
+                file.set_len(end).map_err(|e| format!("Failed to truncate {}: {}", path.display(), e))?;
+            }
+        }
+
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+            frames_written: 0,
+            commands: HashMap::new(),
+        })
+    }
+
+    fn write_record(&mut self, timestamp: u64, kind: u8, payload: &[u8]) -> Result<(), String> {
+        let len = u32::try_from(payload.len()).map_err(|_| "Frame is too large to record")?;
+        self.writer.write_all(&timestamp.to_le_bytes()).map_err(|e| e.to_string())?;
+        self.writer.write_all(&len.to_le_bytes()).map_err(|e| e.to_string())?;
+        self.writer.write_all(&[kind]).map_err(|e| e.to_string())?;
+        self.writer.write_all(payload).map_err(|e| e.to_string())
+    }
+
+    pub fn write_frame(
+        &mut self,
+        timestamp: u64,
+        processes: &[ProcessInfo],
+        system: &SystemStats,
+    ) -> Result<(), String> {
+        let new_commands: Vec<CommandEntry> = processes
+            .iter()
+            .filter(|process| self.commands.get(&(process.pid, process.start_time)) != Some(&process.command))
+            .map(|process| CommandEntry {
+                pid: process.pid,
+                start_time: process.start_time,
+                command: process.command.clone(),
+            })
+            .collect();
+        if !new_commands.is_empty() {
+            let payload = rmp_serde::to_vec_named(&new_commands)
+                .map_err(|e| format!("Failed to encode command lines: {}", e))?;
+            self.write_record(timestamp, COMMAND_RECORD, &payload)?;
+        }
+
+        let slim: Vec<ProcessInfo> = processes
+            .iter()
+            .map(|process| ProcessInfo {
+                command: String::new(),
+                environ: Vec::new(),
+                ..process.clone()
+            })
+            .collect();
+        let payload = rmp_serde::to_vec_named(&FrameRef { processes: &slim, system })
+            .map_err(|e| format!("Failed to encode frame: {}", e))?;
+        self.write_record(timestamp, SNAPSHOT_RECORD, &payload)?;
+        // Flush every frame so a crash loses at most the sample in flight
+        self.writer.flush().map_err(|e| e.to_string())?;
+
+        for entry in new_commands {
+            self.commands.insert((entry.pid, entry.start_time), entry.command);
+        }
+        let live: HashSet<(u32, u64)> = processes.iter().map(|process| (process.pid, process.start_time)).collect();
+        self.commands.retain(|key, _| live.contains(key));
+        self.frames_written += 1;
+        Ok(())
+    }
+}
+
+/// Checks the magic bytes and the format version.
+fn read_header(file: &mut File) -> Result<(), String> {
+    let mut header = [0u8; HEADER_LEN as usize];
+    file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+    file.read_exact(&mut header)
+        .map_err(|_| "Not a NeoHtop recording: file is too short".to_string())?;
+    if &header[..8] != MAGIC {
+        return Err("Not a NeoHtop recording".to_string());
+    }
+    let version = u16::from_le_bytes([header[8], header[9]]);
+    if version != FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported recording format version {} (expected {})",
+            version, FORMAT_VERSION
+        ));
+    }
+    Ok(())
+}
+
+struct FrameIndex {
+    timestamp: u64,
+    offset: u64,
+    len: u32,
+    kind: u8,
+}
+
+/// Indexes every complete record after the header. Also returns where the last
+/// complete record ends, which is short of `file_len` if the final one is torn.
+fn scan_records(reader: &mut (impl Read + Seek), file_len: u64) -> Result<(Vec<FrameIndex>, u64), String> {
+    let mut records = Vec::new();
+    let mut offset = HEADER_LEN;
+    let mut frame_header = [0u8; RECORD_HEADER_LEN as usize];
+    while offset + RECORD_HEADER_LEN <= file_len {
+        reader.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+        reader.read_exact(&mut frame_header).map_err(|e| e.to_string())?;
+        let timestamp = u64::from_le_bytes(frame_header[..8].try_into().unwrap_or_default());
+        let len = u32::from_le_bytes(frame_header[8..12].try_into().unwrap_or_default());
+        let kind = frame_header[12];
+        let payload_offset = offset + RECORD_HEADER_LEN;
+        if payload_offset + len as u64 > file_len {
+            break;
+        }
+        records.push(FrameIndex {
+            timestamp,
+            offset: payload_offset,
+            len,
+            kind,
+        });
+        offset = payload_offset + len as u64;
+    }
+    Ok((records, offset))
+}
+
+#[derive(Default)]
+pub struct RecordingState {
+    recorder: Option<Recorder>,
+    last_error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct RecordingStatus {
+    pub recording: bool,
+    pub path: Option<String>,
+    pub frames_written: u64,
+    /// Why the last recording stopped on its own, if it did
+    pub last_error: Option<String>,
+}
+
+impl RecordingState {
+    fn status(&self) -> RecordingStatus {
+        RecordingStatus {
+            recording: self.recorder.is_some(),
+            path: self
+                .recorder
+                .as_ref()
+                .map(|recorder| recorder.path.display().to_string()),
+            frames_written: self.recorder.as_ref().map_or(0, |recorder| recorder.frames_written),
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+/// Appends a sample to the active recording. A write failure stops the
+/// recording instead of failing the sample, so the live view keeps working.
+pub fn record_frame(
+    state: &AppState,
+    timestamp: u64,
+    processes: &[ProcessInfo],
+    system: &SystemStats,
+) -> Result<(), String> {
+    let mut recording = state.recording.lock().map_err(|_| "Failed to lock recording state")?;
+    if let Some(recorder) = recording.recorder.as_mut() {
+        if let Err(e) = recorder.write_frame(timestamp, processes, system) {
+            recording.recorder = None;
+            recording.last_error = Some(e);
+        }
+    }
+    Ok(())
+}
+
+pub struct Player {
+    path: PathBuf,
+    reader: BufReader<File>,
+    frames: Vec<FrameIndex>,
+    // Every recorded command line by (pid, start_time), oldest first
+    commands: HashMap<(u32, u64), Vec<(u64, String)>>,
+    // The last decoded frame and its index, shared with every caller until the position moves on
+    decoded: Option<(usize, Arc<Snapshot>)>,
+    speed: f64,
+    paused: bool,
+    // Playback position is anchor_position plus wall time since anchor_instant, scaled by speed
+    anchor_position: u64,
+    anchor_instant: Instant,
+}
+
+#[derive(Serialize)]
+pub struct PlaybackStatus {
+    pub path: String,
+    pub start: u64,
+    pub end: u64,
+    pub frame_count: usize,
+    pub position: u64,
+    pub speed: f64,
+    pub paused: bool,
+}
+
+fn read_payload(reader: &mut BufReader<File>, record: &FrameIndex) -> Result<Vec<u8>, String> {
+    let mut payload = vec![0u8; record.len as usize];
+    reader.seek(SeekFrom::Start(record.offset)).map_err(|e| e.to_string())?;
+    reader.read_exact(&mut payload).map_err(|e| e.to_string())?;
+    Ok(payload)
+}
+
+impl Player {
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let mut file = File::open(&path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        read_header(&mut file)?;
+        let file_len = file.metadata().map_err(|e| e.to_string())?.len();
+
+        let mut reader = BufReader::new(file);
+        let (records, _) = scan_records(&mut reader, file_len)?;
+
+        // Command records are small and few, so load them all up front
+        let mut frames = Vec::new();
+        let mut commands: HashMap<(u32, u64), Vec<(u64, String)>> = HashMap::new();
+        for record in records {
+            match record.kind {
+                SNAPSHOT_RECORD => frames.push(record),
+                COMMAND_RECORD => {
+                    let entries: Vec<CommandEntry> = rmp_serde::from_slice(&read_payload(&mut reader, &record)?)
+                        .map_err(|e| format!("Failed to decode recorded command lines: {}", e))?;
+                    for entry in entries {
+                        commands
+                            .entry((entry.pid, entry.start_time))
+                            .or_default()
+                            .push((record.timestamp, entry.command));
+                    }
+                }
+                // Skip record kinds from newer builds
+                _ => {}
+            }
+        }
+
+        let Some(first) = frames.first() else {
+            return Err("Recording contains no frames".to_string());
+        };
+        let anchor_position = first.timestamp;
+
+        Ok(Self {
+            path,
+            reader,
+            frames,
+            commands,
+            decoded: None,
+            speed: 1.0,
+            paused: false,
+            anchor_position,
+            anchor_instant: Instant::now(),
+        })
+    }
+
+    fn start(&self) -> u64 {
+        self.frames.first().map_or(0, |frame| frame.timestamp)
+    }
+
+    fn end(&self) -> u64 {
+        self.frames.last().map_or(0, |frame| frame.timestamp)
+    }
+
+    fn position(&self) -> u64 {
+        if self.paused {
+            return self.anchor_position;
+        }
+        let elapsed_ms = self.anchor_instant.elapsed().as_secs_f64() * 1000.0 * self.speed;
+        (self.anchor_position + elapsed_ms as u64).min(self.end())
+    }
+
+    fn re_anchor(&mut self, position: u64) {
+        self.anchor_position = position.clamp(self.start(), self.end());
+        self.anchor_instant = Instant::now();
+    }
+
+    pub fn seek(&mut self, position: u64) {
+        self.re_anchor(position);
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        let position = self.position();
+        self.re_anchor(position);
+        self.paused = paused;
+    }
+
+    pub fn set_speed(&mut self, speed: f64) -> Result<(), String> {
+        if !(speed > 0.0 && speed <= MAX_PLAYBACK_SPEED) {
+            return Err(format!(
+                "Playback speed must be above 0 and at most {}",
+                MAX_PLAYBACK_SPEED
+            ));
+        }
+        let position = self.position();
+        self.re_anchor(position);
+        self.speed = speed;
+        Ok(())
+    }
+
+    /// Decodes the last frame recorded at or before the playback position.
+    pub fn current_frame(&mut self) -> Result<Arc<Snapshot>, String> {
+        let position = self.position();
+        let index = self
+            .frames
+            .partition_point(|frame| frame.timestamp <= position)
+            .saturating_sub(1);
+        if let Some((decoded_index, snapshot)) = &self.decoded {
+            if *decoded_index == index {
+                return Ok(snapshot.clone());
+            }
+        }
+        let record = &self.frames[index];
+        let timestamp = record.timestamp;
+
+        let payload = read_payload(&mut self.reader, record)?;
+        let mut frame: Frame = rmp_serde::from_slice(&payload)
+            .map_err(|e| format!("Failed to decode recorded frame: {}", e))?;
+        // Snapshots leave command lines out, the command records fill them in
+        for process in frame.processes.iter_mut() {
+            if let Some(command) = self
+                .commands
+                .get(&(process.pid, process.start_time))
+                .and_then(|history| history.iter().rev().find(|(at, _)| *at <= timestamp))
+            {
+                process.command = command.1.clone();
+            }
+        }
+        let snapshot = Arc::new((frame.processes, frame.system));
+        self.decoded = Some((index, snapshot.clone()));
+        Ok(snapshot)
+    }
+
+    pub fn status(&self) -> PlaybackStatus {
+        PlaybackStatus {
+            path: self.path.display().to_string(),
+            start: self.start(),
+            end: self.end(),
+            frame_count: self.frames.len(),
+            position: self.position(),
+            speed: self.speed,
+            paused: self.paused,
+        }
+    }
+}
+
+/// Returns the current playback frame, or None when showing live data.
+pub fn playback_frame(state: &AppState) -> Result<Option<Arc<Snapshot>>, String> {
+    let mut playback = state.playback.lock().map_err(|_| "Failed to lock playback state")?;
+    match playback.as_mut() {
+        Some(player) => player.current_frame().map(Some),
+        None => Ok(None),
+    }
+}
+
+fn with_player<T>(
+    state: &State<'_, AppState>,
+    f: impl FnOnce(&mut Player) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut playback = state.playback.lock().map_err(|_| "Failed to lock playback state")?;
+    let player = playback.as_mut().ok_or("No recording is being played back")?;
+    f(player)
+}
+
+#[tauri::command]
+pub async fn start_recording(path: String, state: State<'_, AppState>) -> Result<RecordingStatus, String> {
+    let recorder = Recorder::open(PathBuf::from(path))?;
+    let mut recording = state.recording.lock().map_err(|_| "Failed to lock recording state")?;
+    recording.recorder = Some(recorder);
+    recording.last_error = None;
+    Ok(recording.status())
+}
+
+#[tauri::command]
+pub async fn stop_recording(state: State<'_, AppState>) -> Result<RecordingStatus, String> {
+    let mut recording = state.recording.lock().map_err(|_| "Failed to lock recording state")?;
+    let status = recording.status();
+    recording.recorder = None;
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn get_recording_status(state: State<'_, AppState>) -> Result<RecordingStatus, String> {
+    let recording = state.recording.lock().map_err(|_| "Failed to lock recording state")?;
+    Ok(recording.status())
+}
+
+/// Switches get_processes from live data to the frames of a recording.
+#[tauri::command]
+pub async fn open_playback(path: String, state: State<'_, AppState>) -> Result<PlaybackStatus, String> {
+    let player = Player::open(PathBuf::from(path))?;
+    let status = player.status();
+    *state.playback.lock().map_err(|_| "Failed to lock playback state")? = Some(player);
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn close_playback(state: State<'_, AppState>) -> Result<(), String> {
+    *state.playback.lock().map_err(|_| "Failed to lock playback state")? = None;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_playback_status(state: State<'_, AppState>) -> Result<Option<PlaybackStatus>, String> {
+    let playback = state.playback.lock().map_err(|_| "Failed to lock playback state")?;
+    Ok(playback.as_ref().map(Player::status))
+}
+
+/// Moves playback to a recorded timestamp (milliseconds since the Unix epoch).
+#[tauri::command]
+pub async fn seek_playback(position: u64, state: State<'_, AppState>) -> Result<PlaybackStatus, String> {
+    with_player(&state, |player| {
+        player.seek(position);
+        Ok(player.status())
+    })
+}
+
+#[tauri::command]
+pub async fn pause_playback(state: State<'_, AppState>) -> Result<PlaybackStatus, String> {
+    with_player(&state, |player| {
+        player.set_paused(true);
+        Ok(player.status())
+    })
+}
+
+#[tauri::command]
+pub async fn resume_playback(state: State<'_, AppState>) -> Result<PlaybackStatus, String> {
+    with_player(&state, |player| {
+        player.set_paused(false);
+        Ok(player.status())
+    })
+}
+
+#[tauri::command]
+pub async fn set_playback_speed(speed: f64, state: State<'_, AppState>) -> Result<PlaybackStatus, String> {
+    with_player(&state, |player| {
+        player.set_speed(speed)?;
+        Ok(player.status())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("neohtop-{}-{}.rec", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn process(pid: u32, command: &str) -> ProcessInfo {
+        serde_json::from_value(json!({
+            "pid": pid, "ppid": 1, "name": "sh", "cpu_usage": 1.5, "memory_usage": 1024,
+            "status": "Running", "user": "root", "command": command, "environ": ["HOME=/root"],
+            "root": "/", "virtual_memory": 4096, "start_time": 100, "run_time": 5,
+            "disk_usage": [0, 0],
+            "disk_io": {
+                "read_bytes_per_sec": 0.0, "write_bytes_per_sec": 0.0,
+                "total_read_bytes": 0, "total_written_bytes": 0
+            }
+        }))
+        .unwrap()
+    }
+
+    fn system() -> SystemStats {
+        serde_json::from_value(json!({
+            "cpu_usage": [10.0], "memory_total": 8, "memory_used": 4, "memory_free": 4,
+            "memory_cached": 0, "uptime": 60, "load_avg": [0.1, 0.2, 0.3],
+            "network_rx_bytes": 0, "network_tx_bytes": 0,
+            "disk_total_bytes": 0, "disk_used_bytes": 0, "disk_free_bytes": 0
+        }))
+        .unwrap()
+    }
+
+    fn record_count(path: &PathBuf) -> usize {
+        let mut file = File::open(path).unwrap();
+        read_header(&mut file).unwrap();
+        let len = file.metadata().unwrap().len();
+        scan_records(&mut BufReader::new(file), len).unwrap().0.len()
+    }
+
+    #[test]
+    fn writes_command_lines_once_and_restores_them() {
+        let path = temp_path("commands");
+        let mut recorder = Recorder::open(path.clone()).unwrap();
+        recorder.write_frame(1_000, &[process(7, "sh -c true")], &system()).unwrap();
+        recorder.write_frame(2_000, &[process(7, "sh -c true")], &system()).unwrap();
+        recorder.write_frame(3_000, &[process(7, "sleep 1")], &system()).unwrap();
+        drop(recorder);
+        // Three snapshots, plus a command record for the start and one for the exec
+        assert_eq!(record_count(&path), 5);
+
+        let mut player = Player::open(path.clone()).unwrap();
+        player.set_paused(true);
+        player.seek(2_500);
+        let frame = player.current_frame().unwrap();
+        let processes = &frame.0;
+        assert_eq!(processes[0].command, "sh -c true");
+        assert!(processes[0].environ.is_empty());
+        player.seek(3_000);
+        assert_eq!(player.current_frame().unwrap().0[0].command, "sleep 1");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn cuts_off_a_torn_record_before_appending() {
+        let path = temp_path("torn");
+        let mut recorder = Recorder::open(path.clone()).unwrap();
+        recorder.write_frame(1_000, &[process(7, "sh")], &system()).unwrap();
+        drop(recorder);
+        let intact = std::fs::metadata(&path).unwrap().len();
+        // A crash mid-write leaves a record header without its payload
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&2_000u64.to_le_bytes()).unwrap();
+        file.write_all(&[0xff, 0, 0]).unwrap();
+        drop(file);
+
+        let mut recorder = Recorder::open(path.clone()).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), intact);
+        recorder.write_frame(3_000, &[process(7, "sh")], &system()).unwrap();
+        drop(recorder);
+
+        let player = Player::open(path.clone()).unwrap();
+        assert_eq!(player.status().frame_count, 2);
+        assert_eq!(player.status().end, 3_000);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{collect_processes, current_snapshot, recording, AppState, ProcessInfo, SystemStats};
+use serde::Serialize;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Carries a delta::ProcessDelta of the receiving window's page, sent only to windows that watch one
+pub const DELTA_EVENT: &str = "process-delta";
+pub const SNAPSHOT_ERROR_EVENT: &str = "process-snapshot-error";
+
+const DEFAULT_INTERVAL_MS: u64 = 1000;
+pub const MIN_INTERVAL_MS: u64 = 250;
+const MAX_INTERVAL_MS: u64 = 60_000;
+// How long a command issued right at startup waits for the first sample
+const FIRST_SAMPLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub type Snapshot = (Vec<ProcessInfo>, SystemStats);
+
+#[derive(Serialize, Clone, Copy)]
+pub struct SamplingConfig {
+    pub interval_ms: u64,
+    pub paused: bool,
+}
+
+/// Shared cadence of the background sampling thread. Every window listens
+/// to the same snapshots instead of polling on its own timer, and commands
+/// read the latest one instead of refreshing the system themselves.
+pub struct Sampler {
+    config: Mutex<SamplingConfig>,
+    changed: Condvar,
+    latest: Mutex<Option<Arc<Snapshot>>>,
+    sampled: Condvar,
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Self {
+            config: Mutex::new(SamplingConfig {
+                interval_ms: DEFAULT_INTERVAL_MS,
+                paused: false,
+            }),
+            changed: Condvar::new(),
+            latest: Mutex::new(None),
+            sampled: Condvar::new(),
+        }
+    }
+}
+
+impl Sampler {
+    /// Returns the most recent live snapshot, waiting for the first one right after startup.
+    pub fn latest(&self) -> Result<Arc<Snapshot>, String> {
+        let latest = self.latest.lock().map_err(|_| "Failed to lock the latest snapshot")?;
+        let (latest, _) = self
+            .sampled
+            .wait_timeout_while(latest, FIRST_SAMPLE_TIMEOUT, |latest| latest.is_none())
+            .map_err(|_| "Failed to lock the latest snapshot")?;
+        latest
+            .clone()
+            .ok_or_else(|| "No snapshot has been sampled yet".to_string())
+    }
+
+    fn publish(&self, snapshot: Snapshot) -> Result<(), String> {
+        *self.latest.lock().map_err(|_| "Failed to lock the latest snapshot")? = Some(Arc::new(snapshot));
+        self.sampled.notify_all();
+        Ok(())
+    }
+}
+
+/// Starts the thread that samples on the configured interval and emits the changes.
+pub fn spawn(app: AppHandle) -> std::io::Result<()> {
+    std::thread::Builder::new()
+        .name("sampler".to_string())
+        .spawn(move || run(app))
+        .map(|_| ())
+}
+
+fn run(app: AppHandle) {
+    let state = app.state::<AppState>();
+    loop {
+        let started = Instant::now();
+        // Live sampling carries on during playback so history, alerts and recording keep up
+        let sampled = collect_processes(&state)
+            .and_then(|(processes, system)| {
+                // Only the sampler's own snapshots are recorded, at its regular cadence
+                recording::record_frame(&state, crate::unix_millis()?, &processes, &system)?;
+                state.sampler.publish((processes, system))
+            })
+            .and_then(|()| Ok((current_snapshot(&state)?, crate::unix_millis()?)));
+        match sampled {
+            Ok((snapshot, timestamp)) => emit_deltas(&app, &state, timestamp, &snapshot),
+            Err(e) => {
+                let _ = app.emit(SNAPSHOT_ERROR_EVENT, e);
+            }
+        }
+
+        if !wait_for_next_sample(&state.sampler, started) {
+            return;
+        }
+    }
+}
+
+// Sends each watching window the delta of its own page, forgetting windows that closed
+fn emit_deltas(app: &AppHandle, state: &AppState, timestamp: u64, snapshot: &Snapshot) {
+    let deltas = match state.views.lock() {
+        Ok(mut views) => {
+            views.retain(|label| app.get_webview_window(label).is_some());
+            views.push(timestamp, snapshot)
+        }
+        Err(_) => {
+            let _ = app.emit(SNAPSHOT_ERROR_EVENT, "Failed to lock process views");
+            return;
+        }
+    };
+    for (label, delta) in deltas {
+        let _ = match delta {
+            Ok(delta) => app.emit_to(label.as_str(), DELTA_EVENT, delta),
+            Err(e) => app.emit_to(label.as_str(), SNAPSHOT_ERROR_EVENT, e),
+        };
+    }
+}
+
+// Sleeps out the rest of the interval, picking up interval changes and pauses
+// as they happen. Returns false if the config lock was poisoned.
+fn wait_for_next_sample(sampler: &Sampler, started: Instant) -> bool {
+    let Ok(mut config) = sampler.config.lock() else {
+        return false;
+    };
+    loop {
+        if config.paused {
+            config = match sampler.changed.wait(config) {
+                Ok(config) => config,
+                Err(_) => return false,
+            };
+            continue;
+        }
+        let remaining = Duration::from_millis(config.interval_ms).saturating_sub(started.elapsed());
+        if remaining.is_zero() {
+            return true;
+        }
+        config = match sampler.changed.wait_timeout(config, remaining) {
+            Ok((config, _)) => config,
+            Err(_) => return false,
+        };
+    }
+}
+
+#[tauri::command]
+pub async fn get_sampling(state: State<'_, AppState>) -> Result<SamplingConfig, String> {
+    let config = state.sampler.config.lock().map_err(|_| "Failed to lock sampling config")?;
+    Ok(*config)
+}
+
+/// Changes the sampling interval and/or pauses sampling for every window.
+#[tauri::command]
+pub async fn set_sampling(
+    interval_ms: Option<u64>,
+    paused: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<SamplingConfig, String> {
+    let mut config = state.sampler.config.lock().map_err(|_| "Failed to lock sampling config")?;
+    if let Some(interval_ms) = interval_ms {
+        if !(MIN_INTERVAL_MS..=MAX_INTERVAL_MS).contains(&interval_ms) {
+            return Err(format!(
+                "Sampling interval must be between {} and {} ms",
+                MIN_INTERVAL_MS, MAX_INTERVAL_MS
+            ));
+        }
+        config.interval_ms = interval_ms;
+    }
+    if let Some(paused) = paused {
+        config.paused = paused;
+    }
+    state.sampler.changed.notify_all();
+    Ok(*config)
+}
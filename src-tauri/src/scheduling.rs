@@ -0,0 +1,541 @@
+#[cfg(unix)]
+use crate::process_control::to_pid_t;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchedPolicy {
+    Other,
+    Batch,
+    Idle,
+    Fifo,
+    Rr,
+}
+
+impl SchedPolicy {
+    pub fn is_realtime(&self) -> bool {
+        matches!(self, SchedPolicy::Fifo | SchedPolicy::Rr)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IoClass {
+    /// No explicit class, the kernel derives best-effort priority from nice
+    None,
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchedulingInfo {
+    pub nice: Option<i32>,
+    pub sched_policy: Option<SchedPolicy>,
+    pub rt_priority: Option<i32>,
+    pub io_priority_class: Option<IoClass>,
+    pub io_priority_level: Option<u8>,
+    /// Indices of the CPUs the process may run on
+    pub cpu_affinity: Option<Vec<usize>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SchedulingUpdate {
+    pub nice: Option<i32>,
+    pub sched_policy: Option<SchedPolicy>,
+    /// Only meaningful for the fifo and rr policies (1-99)
+    pub rt_priority: Option<i32>,
+    pub io_priority_class: Option<IoClass>,
+    /// 0 (highest) to 7 (lowest), ignored for the idle class
+    pub io_priority_level: Option<u8>,
+    /// Linux applies these per thread, so by default only the main thread changes
+    #[serde(default)]
+    pub all_threads: bool,
+}
+
+fn os_error(action: &str, pid: u32) -> String {
+    let err = std::io::Error::last_os_error();
+    match err.raw_os_error() {
+        #[cfg(unix)]
+        Some(libc::EPERM) | Some(libc::EACCES) => format!(
+            "Permission denied: {} for process {} requires elevated privileges (CAP_SYS_NICE or root)",
+            action, pid
+        ),
+        #[cfg(unix)]
+        Some(libc::ESRCH) => format!("No such process: {}", pid),
+        _ => format!("Failed to {} for process {}: {}", action, pid, err),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn clear_errno() {
+    unsafe { *libc::__errno_location() = 0 }
+}
+
+#[cfg(target_os = "macos")]
+fn clear_errno() {
+    unsafe { *libc::__error() = 0 }
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+fn clear_errno() {}
+
+fn check_nice(nice: i32) -> Result<(), String> {
+    if !(-20..=19).contains(&nice) {
+        return Err(format!("Nice value must be between -20 and 19, got {}", nice));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn get_nice(pid: u32) -> Option<i32> {
+    let raw_pid = to_pid_t(pid).ok()?;
+    // -1 is a valid nice value, so errno is the only way to tell failure apart
+    clear_errno();
+    let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, raw_pid as libc::id_t) };
+    if nice == -1 && std::io::Error::last_os_error().raw_os_error().unwrap_or(0) != 0 {
+        None
+    } else {
+        Some(nice)
+    }
+}
+
+#[cfg(not(unix))]
+fn get_nice(_pid: u32) -> Option<i32> {
+    None
+}
+
+#[cfg(unix)]
+fn set_nice(pid: u32, nice: i32) -> Result<(), String> {
+    check_nice(nice)?;
+    let raw_pid = to_pid_t(pid)?;
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, raw_pid as libc::id_t, nice) } != 0 {
+        return Err(os_error("setting the nice value", pid));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_nice(_pid: u32, _nice: i32) -> Result<(), String> {
+    Err("Changing the nice value is not supported on this platform".to_string())
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{os_error, IoClass, SchedPolicy};
+    use crate::process_control::to_pid_t;
+
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+    const IOPRIO_PRIO_MASK: libc::c_int = (1 << IOPRIO_CLASS_SHIFT) - 1;
+
+    pub fn policy_from_raw(raw_policy: libc::c_int) -> Option<SchedPolicy> {
+        match raw_policy & !libc::SCHED_RESET_ON_FORK {
+            libc::SCHED_OTHER => Some(SchedPolicy::Other),
+            libc::SCHED_BATCH => Some(SchedPolicy::Batch),
+            libc::SCHED_IDLE => Some(SchedPolicy::Idle),
+            libc::SCHED_FIFO => Some(SchedPolicy::Fifo),
+            libc::SCHED_RR => Some(SchedPolicy::Rr),
+            _ => None,
+        }
+    }
+
+    pub fn policy_to_raw(policy: SchedPolicy) -> libc::c_int {
+        match policy {
+            SchedPolicy::Other => libc::SCHED_OTHER,
+            SchedPolicy::Batch => libc::SCHED_BATCH,
+            SchedPolicy::Idle => libc::SCHED_IDLE,
+            SchedPolicy::Fifo => libc::SCHED_FIFO,
+            SchedPolicy::Rr => libc::SCHED_RR,
+        }
+    }
+
+    pub fn io_priority_from_raw(value: libc::c_int) -> Option<(IoClass, u8)> {
+        let class = match value >> IOPRIO_CLASS_SHIFT {
+            0 => IoClass::None,
+            1 => IoClass::Realtime,
+            2 => IoClass::BestEffort,
+            3 => IoClass::Idle,
+            _ => return None,
+        };
+        Some((class, (value & IOPRIO_PRIO_MASK) as u8))
+    }
+
+    pub fn io_priority_to_raw(class: IoClass, level: u8) -> libc::c_int {
+        let (raw_class, level) = match class {
+            IoClass::None => (0, 0),
+            IoClass::Realtime => (1, level),
+            IoClass::BestEffort => (2, level),
+            IoClass::Idle => (3, 0),
+        };
+        (raw_class << IOPRIO_CLASS_SHIFT) | level as libc::c_int
+    }
+
+    pub fn get_policy(pid: u32) -> Option<(SchedPolicy, i32)> {
+        let raw_pid = to_pid_t(pid).ok()?;
+        let policy = unsafe { libc::sched_getscheduler(raw_pid) };
+        if policy < 0 {
+            return None;
+        }
+        let policy = policy_from_raw(policy)?;
+
+        let mut param = libc::sched_param { sched_priority: 0 };
+        if unsafe { libc::sched_getparam(raw_pid, &mut param) } != 0 {
+            return None;
+        }
+        Some((policy, param.sched_priority))
+    }
+
+    pub fn set_policy(pid: u32, policy: SchedPolicy, rt_priority: i32) -> Result<(), String> {
+        let raw_pid = to_pid_t(pid)?;
+        let param = libc::sched_param {
+            sched_priority: if policy.is_realtime() { rt_priority } else { 0 },
+        };
+        if unsafe { libc::sched_setscheduler(raw_pid, policy_to_raw(policy), &param) } != 0 {
+            return Err(os_error("changing the scheduling policy", pid));
+        }
+        Ok(())
+    }
+
+    pub fn get_io_priority(pid: u32) -> Option<(IoClass, u8)> {
+        let raw_pid = to_pid_t(pid).ok()?;
+        let value = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, raw_pid) };
+        if value < 0 {
+            return None;
+        }
+        io_priority_from_raw(value as libc::c_int)
+    }
+
+    pub fn set_io_priority(pid: u32, class: IoClass, level: u8) -> Result<(), String> {
+        let raw_pid = to_pid_t(pid)?;
+        let result = unsafe {
+            libc::syscall(
+                libc::SYS_ioprio_set,
+                IOPRIO_WHO_PROCESS,
+                raw_pid,
+                io_priority_to_raw(class, level),
+            )
+        };
+        if result != 0 {
+            return Err(os_error("changing the I/O priority", pid));
+        }
+        Ok(())
+    }
+
+    pub fn online_cpus() -> usize {
+        let count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+        usize::try_from(count).unwrap_or(0)
+    }
+
+    pub fn get_affinity(pid: u32) -> Option<Vec<usize>> {
+        let raw_pid = to_pid_t(pid).ok()?;
+        let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        let result = unsafe {
+            libc::sched_getaffinity(
+                raw_pid,
+                std::mem::size_of::<libc::cpu_set_t>(),
+                &mut set,
+            )
+        };
+        if result != 0 {
+            return None;
+        }
+        Some(
+            (0..libc::CPU_SETSIZE as usize)
+                .filter(|cpu| unsafe { libc::CPU_ISSET(*cpu, &set) })
+                .collect(),
+        )
+    }
+
+    pub fn set_affinity(pid: u32, cpus: &[usize]) -> Result<(), String> {
+        let raw_pid = to_pid_t(pid)?;
+        let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        for cpu in cpus {
+            if *cpu >= libc::CPU_SETSIZE as usize {
+                return Err(format!("CPU index {} is out of range", cpu));
+            }
+            unsafe { libc::CPU_SET(*cpu, &mut set) };
+        }
+        let result = unsafe {
+            libc::sched_setaffinity(
+                raw_pid,
+                std::mem::size_of::<libc::cpu_set_t>(),
+                &set,
+            )
+        };
+        if result != 0 {
+            return Err(os_error("changing the CPU affinity", pid));
+        }
+        Ok(())
+    }
+}
+
+/// Lists the thread IDs of a process, falling back to the PID itself.
+pub fn thread_ids(pid: u32) -> Result<Vec<u32>, String> {
+    #[cfg(unix)]
+    to_pid_t(pid)?;
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(entries) = std::fs::read_dir(format!("/proc/{}/task", pid)) {
+            let mut tids: Vec<u32> = entries
+                .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+                .collect();
+            if !tids.is_empty() {
+                tids.sort_unstable();
+                return Ok(tids);
+            }
+        }
+    }
+    Ok(vec![pid])
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn check_cpus(cpus: &[usize], online: usize) -> Result<(), String> {
+    if cpus.is_empty() {
+        return Err("At least one CPU must be selected".to_string());
+    }
+    match cpus.iter().find(|cpu| **cpu >= online) {
+        Some(cpu) => Err(format!(
+            "CPU index {} is out of range, {} CPUs are online",
+            cpu, online
+        )),
+        None => Ok(()),
+    }
+}
+
+pub fn read_scheduling(pid: u32) -> SchedulingInfo {
+    #[allow(unused_mut)]
+    let mut info = SchedulingInfo {
+        nice: get_nice(pid),
+        ..Default::default()
+    };
+
+    #[cfg(target_os = "linux")]
+    {
+        if let Some((policy, rt_priority)) = linux::get_policy(pid) {
+            info.sched_policy = Some(policy);
+            info.rt_priority = Some(rt_priority);
+        }
+        if let Some((class, level)) = linux::get_io_priority(pid) {
+            info.io_priority_class = Some(class);
+            info.io_priority_level = Some(level);
+        }
+        info.cpu_affinity = linux::get_affinity(pid);
+    }
+
+    info
+}
+
+fn apply_update(tid: u32, update: &SchedulingUpdate) -> Result<(), String> {
+    // Policy first: switching away from a realtime policy can change what nice is allowed
+    if let Some(policy) = update.sched_policy {
+        #[cfg(target_os = "linux")]
+        linux::set_policy(tid, policy, update.rt_priority.unwrap_or(0))?;
+        #[cfg(not(target_os = "linux"))]
+        return Err(format!(
+            "Setting the {:?} scheduling policy is not supported on this platform",
+            policy
+        ));
+    }
+
+    if let Some(nice) = update.nice {
+        set_nice(tid, nice)?;
+    }
+
+    if let Some(class) = update.io_priority_class {
+        #[cfg(target_os = "linux")]
+        linux::set_io_priority(tid, class, update.io_priority_level.unwrap_or(4))?;
+        #[cfg(not(target_os = "linux"))]
+        return Err(format!(
+            "Setting the {:?} I/O class is not supported on this platform",
+            class
+        ));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_scheduling(pid: u32) -> Result<SchedulingInfo, String> {
+    #[cfg(unix)]
+    to_pid_t(pid)?;
+    let info = read_scheduling(pid);
+    if info.nice.is_none() && info.sched_policy.is_none() {
+        return Err(format!("No such process: {}", pid));
+    }
+    Ok(info)
+}
+
+// Rejects an update before any part of it is applied to any thread
+fn check_update(update: &SchedulingUpdate) -> Result<(), String> {
+    if let Some(nice) = update.nice {
+        check_nice(nice)?;
+    }
+    if let Some(policy) = update.sched_policy {
+        let rt_priority = update.rt_priority.unwrap_or(0);
+        if policy.is_realtime() && !(1..=99).contains(&rt_priority) {
+            return Err(format!(
+                "Realtime priority must be between 1 and 99, got {}",
+                rt_priority
+            ));
+        }
+    }
+    match (update.io_priority_class, update.io_priority_level) {
+        (None, Some(_)) => Err("io_priority_level requires io_priority_class".to_string()),
+        (_, Some(level)) if level > 7 => Err(format!(
+            "I/O priority level must be between 0 and 7, got {}",
+            level
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub async fn set_scheduling(pid: u32, update: SchedulingUpdate) -> Result<SchedulingInfo, String> {
+    check_update(&update)?;
+    #[cfg(unix)]
+    to_pid_t(pid)?;
+
+    let targets = if update.all_threads {
+        thread_ids(pid)?
+    } else {
+        vec![pid]
+    };
+    for tid in targets {
+        apply_update(tid, &update)?;
+    }
+
+    Ok(read_scheduling(pid))
+}
+
+/// Pins a process, or every one of its threads, to the given CPUs.
+#[tauri::command]
+pub async fn set_cpu_affinity(
+    pid: u32,
+    cpus: Vec<usize>,
+    all_threads: Option<bool>,
+) -> Result<Vec<usize>, String> {
+    #[cfg(target_os = "linux")]
+    {
+        to_pid_t(pid)?;
+        check_cpus(&cpus, linux::online_cpus())?;
+        let targets = if all_threads.unwrap_or(false) {
+            thread_ids(pid)?
+        } else {
+            vec![pid]
+        };
+        for tid in targets {
+            linux::set_affinity(tid, &cpus)?;
+        }
+        linux::get_affinity(pid).ok_or_else(|| format!("No such process: {}", pid))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (pid, cpus, all_threads);
+        Err("Setting CPU affinity is not supported on this platform".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update() -> SchedulingUpdate {
+        SchedulingUpdate::default()
+    }
+
+    #[test]
+    fn checks_updates_before_applying_them() {
+        assert!(check_update(&update()).is_ok());
+        assert!(check_update(&SchedulingUpdate { nice: Some(-20), ..update() }).is_ok());
+        assert!(check_update(&SchedulingUpdate { nice: Some(20), ..update() }).is_err());
+        assert!(check_update(&SchedulingUpdate { nice: Some(-21), ..update() }).is_err());
+
+        let fifo = |rt_priority| SchedulingUpdate {
+            sched_policy: Some(SchedPolicy::Fifo),
+            rt_priority,
+            ..update()
+        };
+        assert!(check_update(&fifo(Some(99))).is_ok());
+        assert!(check_update(&fifo(Some(100))).is_err());
+        assert!(check_update(&fifo(None)).is_err());
+        // Priority is ignored outside the realtime policies
+        let batch = SchedulingUpdate {
+            sched_policy: Some(SchedPolicy::Batch),
+            ..update()
+        };
+        assert!(check_update(&batch).is_ok());
+
+        let io = |io_priority_class, io_priority_level| SchedulingUpdate {
+            io_priority_class,
+            io_priority_level,
+            ..update()
+        };
+        assert!(check_update(&io(Some(IoClass::BestEffort), Some(7))).is_ok());
+        assert!(check_update(&io(Some(IoClass::BestEffort), Some(8))).is_err());
+        assert!(check_update(&io(None, Some(0))).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn rejects_pids_that_mean_the_caller() {
+        for pid in [0, u32::MAX] {
+            assert!(get_nice(pid).is_none());
+            assert!(set_nice(pid, 0).is_err());
+            assert!(linux::get_policy(pid).is_none());
+            assert!(linux::set_policy(pid, SchedPolicy::Other, 0).is_err());
+            assert!(linux::get_io_priority(pid).is_none());
+            assert!(linux::set_io_priority(pid, IoClass::None, 0).is_err());
+        }
+    }
+
+    #[test]
+    fn checks_cpu_lists() {
+        assert!(check_cpus(&[0, 3], 4).is_ok());
+        assert!(check_cpus(&[], 4).is_err());
+        assert!(check_cpus(&[0, 4], 4).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn affinity_rejects_pids_that_mean_the_caller() {
+        for pid in [0, u32::MAX] {
+            assert!(thread_ids(pid).is_err());
+            assert!(linux::get_affinity(pid).is_none());
+            assert!(linux::set_affinity(pid, &[0]).is_err());
+        }
+        assert!(linux::online_cpus() > 0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn converts_policies_and_io_classes() {
+        for policy in [
+            SchedPolicy::Other,
+            SchedPolicy::Batch,
+            SchedPolicy::Idle,
+            SchedPolicy::Fifo,
+            SchedPolicy::Rr,
+        ] {
+            assert_eq!(linux::policy_from_raw(linux::policy_to_raw(policy)), Some(policy));
+        }
+        let reset_on_fork = libc::SCHED_FIFO | libc::SCHED_RESET_ON_FORK;
+        assert_eq!(linux::policy_from_raw(reset_on_fork), Some(SchedPolicy::Fifo));
+        assert_eq!(linux::policy_from_raw(42), None);
+
+        assert_eq!(linux::io_priority_to_raw(IoClass::BestEffort, 4), (2 << 13) | 4);
+        for (class, level) in [
+            (IoClass::None, 0),
+            (IoClass::Realtime, 0),
+            (IoClass::BestEffort, 7),
+            (IoClass::Idle, 0),
+        ] {
+            let raw = linux::io_priority_to_raw(class, level);
+            assert_eq!(linux::io_priority_from_raw(raw), Some((class, level)));
+        }
+        // The idle and default classes have no level
+        assert_eq!(linux::io_priority_to_raw(IoClass::Idle, 5), 3 << 13);
+        assert_eq!(linux::io_priority_from_raw(4 << 13), None);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::process_state::ProcessState;
+use crate::procfs::Stat;
+use crate::AppState;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::State;
+
+// Without an earlier sample we take two this far apart to get a usable delta
+const BOOTSTRAP_INTERVAL: Duration = Duration::from_millis(250);
+// Samples older than this no longer describe "current" usage
+const SAMPLE_MAX_AGE: Duration = Duration::from_secs(30);
+
+/// CPU time per thread from the previous `get_threads` call for one process.
+pub struct ThreadSample {
+    taken_at: Instant,
+    cpu_ticks: HashMap<u32, u64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ThreadInfo {
+    pub tid: u32,
+    pub name: String,
+    pub status: String,
+    pub state: ProcessState,
+    pub state_letter: char,
+    pub cpu_usage: f32,
+    /// CPU the thread last ran on
+    pub last_cpu: Option<u32>,
+    pub user_time_ms: u64,
+    pub system_time_ms: u64,
+}
+
+// sysinfo's Process::tasks leaves out the main thread, so take the kernel's own count
+#[cfg(target_os = "linux")]
+pub fn thread_count(_pid: u32, stat: Option<&Stat>) -> Option<u32> {
+    stat.map(|stat| stat.num_threads)
+}
+
+#[cfg(target_os = "macos")]
+pub fn thread_count(pid: u32, _stat: Option<&Stat>) -> Option<u32> {
+    let mut info: libc::proc_taskinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+    let written = unsafe {
+        libc::proc_pidinfo(
+            pid as libc::c_int,
+            libc::PROC_PIDTASKINFO,
+            0,
+            &mut info as *mut _ as *mut libc::c_void,
+            size,
+        )
+    };
+    (written == size).then_some(info.pti_threadnum as u32)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn thread_count(_pid: u32, _stat: Option<&Stat>) -> Option<u32> {
+    None
+}
+
+// Percent of one CPU a thread used between two readings of its tick counter. A thread
+// without an earlier reading, or whose counter went back because the TID was reused, shows 0.
+#[cfg(any(target_os = "linux", test))]
+fn cpu_percent(ticks: u64, before: Option<u64>, elapsed_secs: f64, ticks_per_second: f64) -> f32 {
+    let delta = before.map_or(0, |before| ticks.saturating_sub(before));
+    if elapsed_secs > 0.0 {
+        (delta as f64 / ticks_per_second / elapsed_secs * 100.0) as f32
+    } else {
+        0.0
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_tasks(pid: u32) -> Result<Vec<(u32, Stat)>, String> {
+    let tasks = crate::scheduling::thread_ids(pid)?
+        .into_iter()
+        .filter_map(|tid| Some((tid, crate::procfs::read_task_stat(pid, tid)?)))
+        .collect::<Vec<_>>();
+    if tasks.is_empty() {
+        return Err(format!("No such process: {}", pid));
+    }
+    Ok(tasks)
+}
+
+#[cfg(target_os = "linux")]
+fn list_threads(pid: u32, previous: Option<ThreadSample>) -> Result<(Vec<ThreadInfo>, ThreadSample), String> {
+    let previous = match previous.filter(|sample| sample.taken_at.elapsed() < SAMPLE_MAX_AGE) {
+        Some(sample) => sample,
+        None => {
+            let sample = ThreadSample {
+                taken_at: Instant::now(),
+                cpu_ticks: read_tasks(pid)?
+                    .into_iter()
+                    .map(|(tid, task)| (tid, task.utime + task.stime))
+                    .collect(),
+            };
+            std::thread::sleep(BOOTSTRAP_INTERVAL);
+            sample
+        }
+    };
+
+    let tasks = read_tasks(pid)?;
+    let taken_at = Instant::now();
+    let elapsed = taken_at.duration_since(previous.taken_at).as_secs_f64();
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64;
+    let ms_per_tick = 1000.0 / ticks_per_second;
+
+    let threads = tasks
+        .iter()
+        .map(|(tid, task)| {
+            let before = previous.cpu_ticks.get(tid).copied();
+            let state = ProcessState::from_letter(task.state);
+            ThreadInfo {
+                tid: *tid,
+                name: task.comm.clone(),
+                status: state.label().to_string(),
+                state,
+                state_letter: task.state,
+                cpu_usage: cpu_percent(task.utime + task.stime, before, elapsed, ticks_per_second),
+                last_cpu: task.processor,
+                user_time_ms: (task.utime as f64 * ms_per_tick) as u64,
+                system_time_ms: (task.stime as f64 * ms_per_tick) as u64,
+            }
+        })
+        .collect();
+
+    let sample = ThreadSample {
+        taken_at,
+        cpu_ticks: tasks
+            .into_iter()
+            .map(|(tid, task)| (tid, task.utime + task.stime))
+            .collect(),
+    };
+    Ok((threads, sample))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn list_threads(_pid: u32, _previous: Option<ThreadSample>) -> Result<(Vec<ThreadInfo>, ThreadSample), String> {
+    Err("Listing threads is not supported on this platform".to_string())
+}
+
+#[tauri::command]
+pub async fn get_threads(pid: u32, state: State<'_, AppState>) -> Result<Vec<ThreadInfo>, String> {
+    let previous = {
+        let mut samples = state.thread_samples.lock().map_err(|_| "Failed to lock thread samples")?;
+        samples.retain(|_, sample| sample.taken_at.elapsed() < SAMPLE_MAX_AGE);
+        samples.remove(&pid)
+    };
+
+    let (threads, sample) = tauri::async_runtime::spawn_blocking(move || list_threads(pid, previous))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    state
+        .thread_samples
+        .lock()
+        .map_err(|_| "Failed to lock thread samples")?
+        .insert(pid, sample);
+
+    Ok(threads)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_percent_is_relative_to_one_cpu() {
+        assert_eq!(cpu_percent(150, Some(100), 1.0, 100.0), 50.0);
+        assert_eq!(cpu_percent(300, Some(100), 0.5, 100.0), 400.0);
+        assert_eq!(cpu_percent(30, Some(0), 0.25, 250.0), 48.0);
+    }
+
+    #[test]
+    fn cpu_percent_is_zero_without_a_usable_delta() {
+        assert_eq!(cpu_percent(150, None, 1.0, 100.0), 0.0);
+        assert_eq!(cpu_percent(50, Some(100), 1.0, 100.0), 0.0);
+        assert_eq!(cpu_percent(150, Some(100), 0.0, 100.0), 0.0);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::procfs::Status;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// Accounts can be added while we run, e.g. by a package install, so misses are retried after this
+const MISS_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GroupEntry {
+    pub gid: u32,
+    pub name: String,
+}
+
+/// Who a process runs as. Names fall back to the numeric ID when the user
+/// database has no entry.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Credentials {
+    pub real_uid: u32,
+    pub effective_uid: u32,
+    /// Saved IDs and supplementary groups are only known on Linux
+    pub saved_uid: Option<u32>,
+    pub real_gid: u32,
+    pub effective_gid: u32,
+    pub saved_gid: Option<u32>,
+    pub real_user: String,
+    pub effective_user: String,
+    pub effective_group: String,
+    pub groups: Vec<GroupEntry>,
+    /// The effective UID differs from the real one, e.g. after running a setuid binary
+    pub setuid: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Ids {
+    pub real_uid: u32,
+    pub effective_uid: u32,
+    pub saved_uid: Option<u32>,
+    pub real_gid: u32,
+    pub effective_gid: u32,
+    pub saved_gid: Option<u32>,
+    pub groups: Vec<u32>,
+}
+
+/// The IDs sysinfo already knows, used where /proc/<pid>/status is not available.
+#[cfg(unix)]
+pub fn sysinfo_ids(process: &sysinfo::Process) -> Option<Ids> {
+    use sysinfo::ProcessExt;
+    let real_uid = **process.user_id()?;
+    let real_gid = *process.group_id()?;
+    Some(Ids {
+        real_uid,
+        effective_uid: process.effective_user_id().map_or(real_uid, |uid| **uid),
+        real_gid,
+        effective_gid: process.effective_group_id().map_or(real_gid, |gid| *gid),
+        ..Ids::default()
+    })
+}
+
+#[cfg(not(unix))]
+pub fn sysinfo_ids(_process: &sysinfo::Process) -> Option<Ids> {
+    None
+}
+
+/// All IDs, including the saved ones and supplementary groups, from a parsed /proc/<pid>/status.
+pub fn proc_ids(status: &Status) -> Ids {
+    let (real_uid, effective_uid, saved_uid) = status.uids;
+    let (real_gid, effective_gid, saved_gid) = status.gids;
+    Ids {
+        real_uid,
+        effective_uid,
+        saved_uid: Some(saved_uid),
+        real_gid,
+        effective_gid,
+        saved_gid: Some(saved_gid),
+        groups: status.groups.clone(),
+    }
+}
+
+#[cfg(unix)]
+mod lookup {
+    use std::ffi::CStr;
+
+    // Entries with huge member lists can need more than the suggested size
+    const MAX_BUFFER_SIZE: usize = 1 << 20;
+
+    fn initial_buffer_size(name: libc::c_int) -> usize {
+        match unsafe { libc::sysconf(name) } {
+            size if size > 0 => size as usize,
+            _ => 1024,
+        }
+    }
+
+    // Runs a getpwuid_r style call, growing the buffer while it reports ERANGE
+    fn with_buffer(name: libc::c_int, mut call: impl FnMut(&mut [u8]) -> Result<Option<String>, i32>) -> Option<String> {
+        let mut buffer = vec![0u8; initial_buffer_size(name)];
+        loop {
+            match call(&mut buffer) {
+                Err(libc::ERANGE) if buffer.len() < MAX_BUFFER_SIZE => {
+                    let size = buffer.len() * 2;
+                    buffer.resize(size, 0);
+                }
+                Err(_) => return None,
+                Ok(name) => return name,
+            }
+        }
+    }
+
+    /// Looks the user up through libc, so NSS sources like LDAP are honoured as well as /etc/passwd.
+    pub fn user_name(uid: u32) -> Option<String> {
+        with_buffer(libc::_SC_GETPW_R_SIZE_MAX, |buffer| {
+            let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+            let mut result: *mut libc::passwd = std::ptr::null_mut();
+            let code = unsafe {
+                libc::getpwuid_r(uid, &mut entry, buffer.as_mut_ptr().cast(), buffer.len(), &mut result)
+            };
+            if code != 0 {
+                return Err(code);
+            }
+            if result.is_null() || entry.pw_name.is_null() {
+                return Ok(None);
+            }
+            Ok(Some(unsafe { CStr::from_ptr(entry.pw_name) }.to_string_lossy().into_owned()))
+        })
+    }
+
+    pub fn group_name(gid: u32) -> Option<String> {
+        with_buffer(libc::_SC_GETGR_R_SIZE_MAX, |buffer| {
+            let mut entry: libc::group = unsafe { std::mem::zeroed() };
+            let mut result: *mut libc::group = std::ptr::null_mut();
+            let code = unsafe {
+                libc::getgrgid_r(gid, &mut entry, buffer.as_mut_ptr().cast(), buffer.len(), &mut result)
+            };
+            if code != 0 {
+                return Err(code);
+            }
+            if result.is_null() || entry.gr_name.is_null() {
+                return Ok(None);
+            }
+            Ok(Some(unsafe { CStr::from_ptr(entry.gr_name) }.to_string_lossy().into_owned()))
+        })
+    }
+}
+
+#[cfg(not(unix))]
+mod lookup {
+    pub fn user_name(_uid: u32) -> Option<String> {
+        None
+    }
+
+    pub fn group_name(_gid: u32) -> Option<String> {
+        None
+    }
+}
+
+// A name, or the time a lookup found none
+#[derive(Clone)]
+enum Entry {
+    Found(String),
+    Missing(Instant),
+}
+
+// Looks `id` up unless a name or a recent miss is cached, falling back to the number itself
+fn cached_name(entries: &mut HashMap<u32, Entry>, id: u32, lookup: fn(u32) -> Option<String>) -> String {
+    let now = Instant::now();
+    let stale = match entries.get(&id) {
+        Some(Entry::Found(_)) => false,
+        Some(Entry::Missing(at)) => now.duration_since(*at) >= MISS_TTL,
+        None => true,
+    };
+    if stale {
+        let entry = lookup(id).map_or(Entry::Missing(now), Entry::Found);
+        entries.insert(id, entry);
+    }
+    match &entries[&id] {
+        Entry::Found(name) => name.clone(),
+        Entry::Missing(_) => id.to_string(),
+    }
+}
+
+/// Remembers user and group names since NSS lookups can hit the network. Misses are
+/// remembered too, for MISS_TTL.
+#[derive(Default)]
+pub struct UserCache {
+    users: HashMap<u32, Entry>,
+    groups: HashMap<u32, Entry>,
+}
+
+impl UserCache {
+    pub fn user_name(&mut self, uid: u32) -> String {
+        cached_name(&mut self.users, uid, lookup::user_name)
+    }
+
+    pub fn group_name(&mut self, gid: u32) -> String {
+        cached_name(&mut self.groups, gid, lookup::group_name)
+    }
+
+    pub fn credentials(&mut self, ids: Ids) -> Credentials {
+        Credentials {
+            real_user: self.user_name(ids.real_uid),
+            effective_user: self.user_name(ids.effective_uid),
+            effective_group: self.group_name(ids.effective_gid),
+            groups: ids
+                .groups
+                .iter()
+                .map(|gid| GroupEntry {
+                    gid: *gid,
+                    name: self.group_name(*gid),
+                })
+                .collect(),
+            setuid: ids.effective_uid != ids.real_uid,
+            real_uid: ids.real_uid,
+            effective_uid: ids.effective_uid,
+            saved_uid: ids.saved_uid,
+            real_gid: ids.real_gid,
+            effective_gid: ids.effective_gid,
+            saved_gid: ids.saved_gid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_one(_id: u32) -> Option<String> {
+        None
+    }
+
+    fn alice(_id: u32) -> Option<String> {
+        Some("alice".to_string())
+    }
+
+    #[test]
+    fn misses_are_retried_after_the_ttl() {
+        let mut entries = HashMap::new();
+        assert_eq!(cached_name(&mut entries, 1000, no_one), "1000");
+        // A fresh miss is trusted, a stale one is looked up again
+        assert_eq!(cached_name(&mut entries, 1000, alice), "1000");
+        if let Some(expired) = Instant::now().checked_sub(MISS_TTL) {
+            entries.insert(1000, Entry::Missing(expired));
+            assert_eq!(cached_name(&mut entries, 1000, alice), "alice");
+            assert_eq!(cached_name(&mut entries, 1000, no_one), "alice");
+        }
+    }
+}
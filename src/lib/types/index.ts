@@ -6,6 +6,10 @@ export interface Process {
   cpu_usage: number;
   memory_usage: number;
   status: string;
+  state?: ProcessState;
+  // Raw kernel state letter such as "D" or "Z", Linux only
+  state_letter?: string;
+  // Real user; credentials.effective_user differs for setuid programs
   user: string;
   command: string;
   threads?: number;
@@ -15,7 +19,72 @@ export interface Process {
   start_time: number;
   run_time: number;
   disk_usage: [number, number];  // [read_bytes, written_bytes]
+  disk_io: DiskIo;
   session_id?: number;
+  nice?: number;
+  sched_policy?: "other" | "batch" | "idle" | "fifo" | "rr";
+  rt_priority?: number;
+  io_priority_class?: "none" | "realtime" | "best_effort" | "idle";
+  io_priority_level?: number;
+  cpu_affinity?: number[];
+  // Refreshed less often than the other fields, it is expensive to read
+  memory_breakdown?: MemoryBreakdown;
+  credentials?: Credentials;
+}
+
+export type ProcessState =
+  | "running"
+  | "sleeping"
+  | "idle"
+  | "disk_sleep"
+  | "stopped"
+  | "tracing"
+  | "zombie"
+  | "dead"
+  | "wakekill"
+  | "waking"
+  | "parked"
+  | "lock_blocked"
+  | "unknown";
+
+export interface Credentials {
+  real_uid: number;
+  effective_uid: number;
+  saved_uid?: number;
+  real_gid: number;
+  effective_gid: number;
+  saved_gid?: number;
+  real_user: string;
+  effective_user: string;
+  effective_group: string;
+  groups: { gid: number; name: string }[];
+  setuid: boolean;
+}
+
+export interface DiskIo {
+  read_bytes_per_sec: number;
+  write_bytes_per_sec: number;
+  total_read_bytes: number;
+  total_written_bytes: number;
+  read_chars?: number;
+  written_chars?: number;
+  read_syscalls?: number;
+  write_syscalls?: number;
+  cancelled_write_bytes?: number;
+}
+
+export interface MemoryBreakdown {
+  rss: number;
+  uss: number;
+  pss: number;
+  shared_clean: number;
+  shared_dirty: number;
+  private_clean: number;
+  private_dirty: number;
+  swap: number;
+  swap_pss: number;
+  anonymous: number;
+  file_backed: number;
 }
 
 export interface SystemStats {
@@ -31,6 +100,72 @@ export interface SystemStats {
   disk_total_bytes: number;
   disk_used_bytes: number;
   disk_free_bytes: number;
+  process_states: Partial<Record<ProcessState, number>>;
+}
+
+// Fields get_processes can sort by, the SortKey enum in query.rs
+export const SORT_KEYS = [
+  "pid",
+  "ppid",
+  "name",
+  "cpu_usage",
+  "memory_usage",
+  "status",
+  "user",
+  "command",
+  "root",
+  "environ",
+  "threads",
+  "virtual_memory",
+  "start_time",
+  "run_time",
+  "disk_usage",
+  "session_id",
+] as const satisfies readonly (keyof Process)[];
+
+export type SortKey = (typeof SORT_KEYS)[number];
+
+export function isSortKey(field: string): field is SortKey {
+  return (SORT_KEYS as readonly string[]).includes(field);
+}
+
+export interface ProcessQuery {
+  sort_by?: SortKey;
+  sort_direction?: "asc" | "desc";
+  status?: string;
+  user?: string;
+  name?: string;
+  // The search box: comma separated terms matched against name (also as a regex), command and PID
+  search?: string;
+  // Commands listed ahead of everything else
+  pinned?: string[];
+  // Filter expression such as `cpu_usage > 10 and user == "postgres"`
+  filter?: string;
+  offset?: number;
+  limit?: number;
+}
+
+export interface ProcessPage {
+  processes: Process[];
+  // Matches before offset and limit are applied
+  total: number;
+  offset: number;
+  system: SystemStats;
+}
+
+export interface ProcessDelta {
+  sequence: number;
+  // Snapshot the delta applies on top of, null for a full resync
+  base: number | null;
+  timestamp: number;
+  added: Process[];
+  changed: (Partial<Process> & { pid: number })[];
+  removed: number[];
+  // PIDs on the page in display order; the selected process may be sent without being listed
+  order: number[];
+  // Matches before offset and limit are applied
+  total: number;
+  system: SystemStats;
 }
 
 export interface Column {
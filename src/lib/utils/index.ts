@@ -20,6 +20,51 @@ export const statusMap: Record<string, ProcessStatus> = {
     emoji: "⌛",
     color: "var(--overlay0)",
   },
+  "Disk Sleep": {
+    label: "Disk Sleep",
+    emoji: "💽",
+    color: "var(--peach)",
+  },
+  "Stopped": {
+    label: "Stopped",
+    emoji: "⏸️",
+    color: "var(--yellow)",
+  },
+  "Tracing": {
+    label: "Tracing",
+    emoji: "🔍",
+    color: "var(--yellow)",
+  },
+  "Zombie": {
+    label: "Zombie",
+    emoji: "🧟",
+    color: "var(--red)",
+  },
+  "Dead": {
+    label: "Dead",
+    emoji: "💀",
+    color: "var(--red)",
+  },
+  "Wakekill": {
+    label: "Wakekill",
+    emoji: "🔪",
+    color: "var(--maroon)",
+  },
+  "Waking": {
+    label: "Waking",
+    emoji: "⏰",
+    color: "var(--teal)",
+  },
+  "Parked": {
+    label: "Parked",
+    emoji: "🅿️",
+    color: "var(--overlay0)",
+  },
+  "Locked": {
+    label: "Locked",
+    emoji: "🔒",
+    color: "var(--peach)",
+  },
   "Unknown": {
     label: "Unknown",
     emoji: "❓",